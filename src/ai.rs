@@ -0,0 +1,261 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::archetypes::MAX_RADIUS;
+use crate::caster::line_of_sight;
+use crate::maze::{Maze, cell_at};
+use crate::pathfinding::shortest_path;
+use crate::player::{Player, PLAYER_RADIUS};
+use crate::rng::Rng;
+use crate::spatial::SpatialIndex;
+use crate::sprites::Sprite;
+
+const PATROL_SPEED: f32 = 120.0; // unidades del mundo por segundo
+const CHASE_SPEED: f32 = 330.0;
+const SIGHT_RANGE: f32 = 640.0;
+const SIGHT_FOV: f32 = PI * 2.0 / 3.0;
+const HEARING_RADIUS: f32 = 160.0;
+const REPATH_INTERVAL: f32 = 0.5;
+const ARRIVE_DISTANCE: f32 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreatureState {
+    Idle,
+    Patrol,
+    Investigate,
+    Chase,
+    Return,
+}
+
+#[derive(Clone)]
+pub struct Brain {
+    pub state: CreatureState,
+    pub home: Vector2,
+    pub waypoints: Vec<Vector2>,
+    pub waypoint: usize,
+    pub path: Vec<Vector2>, // centros de celda pendientes hasta el destino
+    pub target: Option<Vector2>, // última posición conocida del jugador
+    pub timer: f32,
+    pub repath_timer: f32,
+}
+
+impl Brain {
+    pub fn new(home: Vector2, waypoints: Vec<Vector2>) -> Self {
+        Brain {
+            state: CreatureState::Idle,
+            home,
+            waypoints,
+            waypoint: 0,
+            path: Vec::new(),
+            target: None,
+            timer: 1.0,
+            repath_timer: 0.0,
+        }
+    }
+
+    fn enter(&mut self, state: CreatureState, timer: f32) {
+        self.state = state;
+        self.timer = timer;
+        self.path.clear();
+        self.repath_timer = 0.0;
+    }
+}
+
+pub fn update_creatures(
+    sprites: &mut [Sprite],
+    player: &Player,
+    maze: &Maze,
+    block_size: usize,
+    delta_time: f32,
+    rng: &mut Rng,
+) {
+    for sprite in sprites.iter_mut().filter(|s| s.is_alive) {
+        if let Some(mut brain) = sprite.brain.take() {
+            update_creature(sprite, &mut brain, player, maze, block_size, delta_time, rng);
+            sprite.brain = Some(brain);
+        }
+    }
+}
+
+// Tipo de la criatura que está tocando al jugador, si alguna lo toca
+pub fn creature_contact(sprites: &[Sprite], player: &Player, spatial: &SpatialIndex) -> Option<usize> {
+    spatial
+        .query_radius(player.pos, MAX_RADIUS + PLAYER_RADIUS)
+        .into_iter()
+        .map(|i| &sprites[i])
+        .filter(|s| s.is_alive && s.brain.is_some())
+        .find(|s| {
+            let reach = s.radius + PLAYER_RADIUS;
+            let dx = s.pos.x - player.pos.x;
+            let dy = s.pos.y - player.pos.y;
+            dx * dx + dy * dy <= reach * reach
+        })
+        .map(|s| s.archetype)
+}
+
+fn update_creature(
+    sprite: &mut Sprite,
+    brain: &mut Brain,
+    player: &Player,
+    maze: &Maze,
+    block_size: usize,
+    delta_time: f32,
+    rng: &mut Rng,
+) {
+    let to_player = Vector2::new(player.pos.x - sprite.pos.x, player.pos.y - sprite.pos.y);
+    let distance = to_player.x.hypot(to_player.y);
+
+    // Percepción: vista (rango, cono frontal y sin paredes de por medio) y oído (radio)
+    let angle_to_player = normalize_angle(to_player.y.atan2(to_player.x) - sprite.facing);
+    let in_cone = brain.state == CreatureState::Chase || angle_to_player.abs() <= SIGHT_FOV / 2.0;
+    let sees_player = distance <= SIGHT_RANGE
+        && in_cone
+        && line_of_sight(maze, block_size, sprite.pos, player.pos);
+    let hears_player = distance <= HEARING_RADIUS;
+
+    if sees_player {
+        if brain.state != CreatureState::Chase {
+            brain.enter(CreatureState::Chase, 0.0);
+        }
+        brain.target = Some(player.pos);
+    } else if brain.state == CreatureState::Chase {
+        // Perdió de vista al jugador: va a revisar el último lugar donde lo vio
+        brain.enter(CreatureState::Investigate, rng.range(2.0, 4.0));
+    } else if hears_player && brain.state != CreatureState::Investigate {
+        brain.target = Some(player.pos);
+        brain.enter(CreatureState::Investigate, rng.range(2.0, 4.0));
+    }
+
+    match brain.state {
+        CreatureState::Idle => {
+            brain.timer -= delta_time;
+            sprite.facing += rng.range(-1.0, 1.0) * delta_time;
+            if brain.timer <= 0.0 {
+                if brain.waypoints.is_empty() {
+                    brain.timer = rng.range(1.0, 3.0);
+                } else {
+                    brain.enter(CreatureState::Patrol, 0.0);
+                }
+            }
+        }
+        CreatureState::Patrol => {
+            let goal = brain.waypoints[brain.waypoint];
+            if follow(sprite, brain, goal, PATROL_SPEED, maze, block_size, delta_time) {
+                brain.waypoint = (brain.waypoint + 1) % brain.waypoints.len();
+                brain.enter(CreatureState::Idle, rng.range(0.5, 2.0));
+            }
+        }
+        CreatureState::Chase => {
+            let goal = brain.target.unwrap_or(player.pos);
+            follow(sprite, brain, goal, CHASE_SPEED, maze, block_size, delta_time);
+        }
+        CreatureState::Investigate => {
+            let goal = brain.target.unwrap_or(brain.home);
+            if follow(sprite, brain, goal, PATROL_SPEED * 1.5, maze, block_size, delta_time) {
+                // Ya llegó: mira alrededor un rato antes de volver
+                brain.timer -= delta_time;
+                sprite.facing += 2.0 * delta_time;
+                if brain.timer <= 0.0 {
+                    brain.target = None;
+                    brain.enter(CreatureState::Return, 0.0);
+                }
+            }
+        }
+        CreatureState::Return => {
+            let goal = brain.waypoints.get(brain.waypoint).copied().unwrap_or(brain.home);
+            if follow(sprite, brain, goal, PATROL_SPEED, maze, block_size, delta_time) {
+                let next = if brain.waypoints.is_empty() { CreatureState::Idle } else { CreatureState::Patrol };
+                brain.enter(next, rng.range(0.5, 2.0));
+            }
+        }
+    }
+
+    // El estado decide el clip de animación
+    let clip = if distance <= sprite.radius + PLAYER_RADIUS {
+        "attack"
+    } else {
+        match brain.state {
+            CreatureState::Idle => "idle",
+            CreatureState::Chase => "run",
+            _ => "walk",
+        }
+    };
+    sprite.animator.play(clip);
+}
+
+// Avanza hacia `goal` siguiendo el camino de la cuadrícula. Devuelve true al llegar (o si no hay camino).
+fn follow(
+    sprite: &mut Sprite,
+    brain: &mut Brain,
+    goal: Vector2,
+    speed: f32,
+    maze: &Maze,
+    block_size: usize,
+    delta_time: f32,
+) -> bool {
+    let bs = block_size as f32;
+    let to_cell = |p: Vector2| ((p.x / bs) as usize, (p.y / bs) as usize);
+
+    brain.repath_timer -= delta_time;
+    if brain.path.is_empty() || brain.repath_timer <= 0.0 {
+        brain.repath_timer = REPATH_INTERVAL;
+        brain.path = match shortest_path(maze, to_cell(sprite.pos), to_cell(goal)) {
+            Some(cells) => cells
+                .iter()
+                .skip(1)
+                .map(|&(x, y)| Vector2::new((x as f32 + 0.5) * bs, (y as f32 + 0.5) * bs))
+                .collect(),
+            None => return true,
+        };
+        // En la celda final se apunta al punto exacto, no al centro
+        match brain.path.last_mut() {
+            Some(last) => *last = goal,
+            None => brain.path.push(goal),
+        }
+    }
+
+    let next = brain.path[0];
+    let (dx, dy) = (next.x - sprite.pos.x, next.y - sprite.pos.y);
+    let remaining = dx.hypot(dy);
+
+    if remaining <= ARRIVE_DISTANCE {
+        brain.path.remove(0);
+        return brain.path.is_empty();
+    }
+
+    let step = (speed * delta_time).min(remaining);
+    sprite.facing = dy.atan2(dx);
+    move_with_collision(sprite, dx / remaining * step, dy / remaining * step, maze, block_size);
+    false
+}
+
+// Movimiento separado por ejes para deslizarse contra las paredes
+fn move_with_collision(sprite: &mut Sprite, dx: f32, dy: f32, maze: &Maze, block_size: usize) {
+    let next_x = Vector2::new(sprite.pos.x + dx, sprite.pos.y);
+    if is_free(next_x, sprite.radius, maze, block_size) {
+        sprite.pos = next_x;
+    }
+    let next_y = Vector2::new(sprite.pos.x, sprite.pos.y + dy);
+    if is_free(next_y, sprite.radius, maze, block_size) {
+        sprite.pos = next_y;
+    }
+}
+
+fn is_free(pos: Vector2, radius: f32, maze: &Maze, block_size: usize) -> bool {
+    let bs = block_size as f32;
+    [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].iter().all(|(ox, oy)| {
+        let x = ((pos.x + ox * radius) / bs).floor() as i32;
+        let y = ((pos.y + oy * radius) / bs).floor() as i32;
+        cell_at(maze, x, y) == Some(' ')
+    })
+}
+
+fn normalize_angle(mut a: f32) -> f32 {
+    while a > PI {
+        a -= 2.0 * PI;
+    }
+    while a < -PI {
+        a += 2.0 * PI;
+    }
+    a
+}
@@ -0,0 +1,206 @@
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayMode {
+    Loop,
+    Once, // se queda en el último frame y avisa al terminar
+}
+
+// Tramo de frames de una hoja de sprites
+#[derive(Debug, Clone)]
+pub struct Clip {
+    pub name: String,
+    pub first: usize,
+    pub count: usize,
+    pub fps: f32,
+    pub mode: PlayMode,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimEvent {
+    Finished(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClipSet {
+    clips: Vec<Clip>,
+}
+
+impl ClipSet {
+    // Sin archivo .anim: un único clip `idle` que recorre todos los frames (5 por segundo)
+    pub fn single(frame_count: usize) -> Self {
+        ClipSet {
+            clips: vec![Clip {
+                name: "idle".to_string(),
+                first: 0,
+                count: frame_count.max(1),
+                fps: 5.0,
+                mode: PlayMode::Loop,
+            }],
+        }
+    }
+
+    // Formato: una línea por clip, `clip nombre primer_frame cantidad fps loop|once`, con comentarios `#`
+    pub fn load(path: &str, frame_count: usize) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut clips = Vec::new();
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |msg: &str| format!("{}:{}: {}", path, i + 1, msg);
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() != 6 || parts[0] != "clip" {
+                return Err(err("se esperaba `clip nombre primer_frame cantidad fps loop|once`"));
+            }
+
+            let first: usize = parts[2].parse().map_err(|_| err("primer_frame inválido"))?;
+            let count: usize = parts[3].parse().map_err(|_| err("cantidad inválida"))?;
+            let fps: f32 = parts[4].parse().map_err(|_| err("fps inválido"))?;
+            let mode = match parts[5] {
+                "loop" => PlayMode::Loop,
+                "once" => PlayMode::Once,
+                other => return Err(err(&format!("se esperaba loop u once, se recibió '{}'", other))),
+            };
+
+            if count == 0 || first + count > frame_count {
+                return Err(err(&format!("el clip sale de la hoja ({} frames)", frame_count)));
+            }
+            if !fps.is_finite() || fps <= 0.0 {
+                return Err(err("fps debe ser un número mayor que 0"));
+            }
+
+            clips.push(Clip { name: parts[1].to_string(), first, count, fps, mode });
+        }
+
+        Ok(ClipSet { clips })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Clip> {
+        self.clips.iter().find(|c| c.name == name)
+    }
+}
+
+// Reproduce un clip por nombre; el clip se busca en el ClipSet de la hoja al actualizar
+#[derive(Debug, Clone)]
+pub struct Animator {
+    pub clip: String,
+    pub frame: usize, // dentro del clip
+    pub time: f32,
+    pub finished: bool,
+}
+
+impl Animator {
+    pub fn new(clip: &str) -> Self {
+        Animator { clip: clip.to_string(), frame: 0, time: 0.0, finished: false }
+    }
+
+    // Cambia de clip; si ya está sonando no lo reinicia
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            *self = Animator::new(clip);
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32, clips: &ClipSet) -> Option<AnimEvent> {
+        if self.finished {
+            return None;
+        }
+
+        // Un clip que la hoja no define se da por terminado, así nadie queda esperándolo
+        let Some(clip) = clips.get(&self.clip) else {
+            self.finished = true;
+            return Some(AnimEvent::Finished(self.clip.clone()));
+        };
+
+        self.time += delta_time;
+        let frame_duration = 1.0 / clip.fps;
+        while self.time >= frame_duration {
+            self.time -= frame_duration;
+            self.frame += 1;
+
+            if self.frame >= clip.count {
+                match clip.mode {
+                    PlayMode::Loop => self.frame = 0,
+                    PlayMode::Once => {
+                        self.frame = clip.count - 1;
+                        self.finished = true;
+                        return Some(AnimEvent::Finished(self.clip.clone()));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Frame de la hoja que corresponde al estado actual
+    pub fn sheet_frame(&self, clips: &ClipSet) -> Option<usize> {
+        clips.get(&self.clip).map(|c| c.first + self.frame.min(c.count - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempFile;
+
+    #[test]
+    fn loads_clips_and_ignores_comments() {
+        let file = TempFile::new("clips_ok.anim", "# clips\nclip idle 0 2 2 loop\n\nclip pickup 2 2 10 once # al recoger\n");
+        let clips = ClipSet::load(file.path(), 4).unwrap();
+        let idle = clips.get("idle").unwrap();
+        assert_eq!((idle.first, idle.count, idle.mode), (0, 2, PlayMode::Loop));
+        let pickup = clips.get("pickup").unwrap();
+        assert_eq!((pickup.first, pickup.count, pickup.fps, pickup.mode), (2, 2, 10.0, PlayMode::Once));
+        assert!(clips.get("walk").is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_clips() {
+        let cases = [
+            ("clip idle 0 2 2", "se esperaba `clip"),
+            ("anim idle 0 2 2 loop", "se esperaba `clip"),
+            ("clip idle x 2 2 loop", "primer_frame"),
+            ("clip idle 0 2 0 loop", "fps"),
+            ("clip idle 0 2 inf loop", "fps"),
+            ("clip idle 0 2 NaN loop", "fps"),
+            ("clip idle 0 2 2 bounce", "loop u once"),
+            ("clip idle 3 2 2 loop", "sale de la hoja"),
+            ("clip idle 0 0 2 loop", "sale de la hoja"),
+        ];
+        for (i, (line, expected)) in cases.iter().enumerate() {
+            let file = TempFile::new(&format!("clips_bad_{}.anim", i), &format!("\n{}\n", line));
+            let err = ClipSet::load(file.path(), 4).unwrap_err();
+            assert!(err.contains(":2:") && err.contains(expected), "{}: {}", line, err);
+        }
+        assert!(ClipSet::load("no-existe.anim", 4).is_err());
+    }
+
+    #[test]
+    fn once_clips_finish_and_loops_wrap() {
+        let file = TempFile::new("clips_play.anim", "clip walk 0 4 10 loop\nclip pickup 4 2 10 once\n");
+        let clips = ClipSet::load(file.path(), 6).unwrap();
+
+        let mut animator = Animator::new("walk");
+        assert_eq!(animator.update(0.45, &clips), None);
+        assert_eq!(animator.sheet_frame(&clips), Some(0)); // 4 frames y vuelta al primero
+
+        animator.play("pickup");
+        assert_eq!(animator.update(0.15, &clips), None);
+        assert_eq!(animator.sheet_frame(&clips), Some(5));
+        assert_eq!(animator.update(0.1, &clips), Some(AnimEvent::Finished("pickup".to_string())));
+        assert_eq!(animator.update(0.1, &clips), None);
+        assert_eq!(animator.sheet_frame(&clips), Some(5));
+    }
+
+    #[test]
+    fn missing_clips_finish_at_once() {
+        let clips = ClipSet::single(3);
+        assert_eq!(clips.get("idle").map(|c| c.count), Some(3));
+        let mut animator = Animator::new("death");
+        assert_eq!(animator.update(0.0, &clips), Some(AnimEvent::Finished("death".to_string())));
+    }
+}
@@ -0,0 +1,395 @@
+use raylib::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::doors::KeyColor;
+use crate::framebuffer::BlendMode;
+use crate::objectives::Tally;
+use crate::textures::SheetLayout;
+
+pub const ARCHETYPES_FILE: &str = "assets/sprites.def";
+
+// assets/sprites.def tal como estaba al compilar
+const BUILTIN_ARCHETYPES: &str = include_str!("../assets/sprites.def");
+
+// Tope para `radius`: así las consultas al índice espacial tienen un alcance fijo
+pub const MAX_RADIUS: f32 = 40.0;
+
+// Topes de la hoja: los tamaños de la grilla y de la caché de texturas salen de frames × vistas
+pub const MAX_FRAMES: usize = 64;
+pub const MAX_DIRECTIONS: usize = 16;
+
+// Qué hace un sprite en el juego
+#[derive(Debug, Clone, PartialEq)]
+pub enum Behavior {
+    Static,         // decorado (o el premio de la meta)
+    Creature,       // patrulla, persigue y daña al jugador
+    Pickup(String), // se recoge y suma uno al objeto del inventario con ese nombre
+}
+
+// Para qué sirve el objeto de un `behavior pickup`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ItemRoles {
+    pub counts_for: Option<Tally>, // objetivo `gems` o `runes` al que suma
+    pub opens: Option<KeyColor>,   // color de las puertas que abre
+}
+
+// Tipo de sprite definido en `assets/sprites.def`
+#[derive(Debug, Clone)]
+pub struct Archetype {
+    pub name: String,  // el que usan las líneas `sprite` de los niveles
+    pub label: String, // nombre para mostrar (mensajes, leyenda del mapa)
+    pub texture: String,
+    pub frames: usize,
+    pub directions: usize,
+    pub layout: SheetLayout,
+    pub tint: Option<Color>,
+    pub clips: Option<String>, // si no, `<textura>.anim`
+    pub sound: Option<String>, // sonido de proximidad
+    pub scare_sound: Option<String>, // jump-scare cuando una criatura de este tipo mata al jugador
+    pub radius: f32,           // choque con las paredes y alcance del contacto o la recogida
+    pub size: f32,             // alto en unidades del mundo
+    pub z_offset: f32,         // altura de la base sobre el piso
+    pub blend: BlendMode,
+    pub opacity: f32,
+    pub behavior: Behavior,
+    pub roles: ItemRoles,
+    pub map_color: Color,
+    pub effect: Option<String>, // emisor de partículas a su alrededor
+}
+
+impl Archetype {
+    fn new(name: &str) -> Self {
+        Archetype {
+            name: name.to_string(),
+            label: name.to_string(),
+            texture: String::new(),
+            frames: 1,
+            directions: 1,
+            layout: SheetLayout::default(),
+            tint: None,
+            clips: None,
+            sound: None,
+            scare_sound: None,
+            radius: 16.0,
+            size: 60.0,
+            z_offset: 0.0,
+            blend: BlendMode::Alpha,
+            opacity: 1.0,
+            behavior: Behavior::Static,
+            roles: ItemRoles::default(),
+            map_color: Color::WHITE,
+            effect: None,
+        }
+    }
+
+    pub fn clips_path(&self) -> PathBuf {
+        match &self.clips {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(&self.texture).with_extension("anim"),
+        }
+    }
+
+    // Objeto del inventario que da al recogerlo
+    pub fn item(&self) -> Option<&str> {
+        match &self.behavior {
+            Behavior::Pickup(item) => Some(item),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Archetypes {
+    list: Vec<Archetype>,
+}
+
+impl Archetypes {
+    // Formato: `archetype nombre` abre un tipo y las líneas siguientes (`clave valores...`) lo describen.
+    // Comentarios con `#`.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(path, &content)
+    }
+
+    // Los tipos con los que se compiló el juego (ver `Manifest::builtin`)
+    pub fn builtin() -> Self {
+        Self::parse(ARCHETYPES_FILE, BUILTIN_ARCHETYPES).expect("los tipos de sprite incluidos deben ser válidos")
+    }
+
+    fn parse(path: &str, content: &str) -> Result<Self, String> {
+        let mut list: Vec<Archetype> = Vec::new();
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut parts = line.split_whitespace();
+            let Some(key) = parts.next() else { continue };
+            let values: Vec<&str> = parts.collect();
+
+            let err = |msg: &str| format!("{}:{}: {}", path, i + 1, msg);
+            let num = |idx: usize| -> Result<f32, String> {
+                values
+                    .get(idx)
+                    .and_then(|v| v.parse::<f32>().ok())
+                    .ok_or_else(|| err(&format!("valor numérico inválido en '{}'", key)))
+            };
+            let count = |max: usize| -> Result<usize, String> {
+                let n = num(0)?;
+                if !(1.0..=max as f32).contains(&n) {
+                    return Err(err(&format!("{}: debe estar entre 1 y {}", key, max)));
+                }
+                Ok(n as usize)
+            };
+            let text = || values.first().map(|v| v.to_string()).ok_or_else(|| err(&format!("{}: falta el valor", key)));
+            let color = || -> Result<Color, String> {
+                let channel = |idx: usize| num(idx).map(|v| v.clamp(0.0, 255.0) as u8);
+                Ok(Color::new(channel(0)?, channel(1)?, channel(2)?, 255))
+            };
+
+            if key == "archetype" {
+                let name = text()?;
+                if list.iter().any(|a| a.name == name) {
+                    return Err(err(&format!("tipo '{}' repetido", name)));
+                }
+                list.push(Archetype::new(&name));
+                continue;
+            }
+
+            let archetype = list.last_mut().ok_or_else(|| err("se esperaba una línea archetype"))?;
+            match key {
+                "label" => archetype.label = values.join(" "),
+                "texture" => archetype.texture = text()?,
+                "frames" => archetype.frames = count(MAX_FRAMES)?,
+                "directions" => archetype.directions = count(MAX_DIRECTIONS)?,
+                "frame_size" => archetype.layout.frame_size = Some((num(0)? as u32, num(1)? as u32)),
+                "columns" => archetype.layout.columns = Some((num(0)? as usize).max(1)),
+                "pivot" => archetype.layout.pivot = Some(Vector2::new(num(0)?, num(1)?)),
+                "frame" => {
+                    // `frame n x y ancho alto [pivote_x pivote_y]`
+                    let rect = Rectangle::new(num(1)?, num(2)?, num(3)?, num(4)?);
+                    let pivot = if values.len() > 6 { Some(Vector2::new(num(5)?, num(6)?)) } else { None };
+                    archetype.layout.frames.push((num(0)? as usize, rect, pivot));
+                }
+                "tint" => archetype.tint = Some(color()?),
+                "clips" => archetype.clips = Some(text()?),
+                "sound" => archetype.sound = Some(text()?),
+                "scare_sound" => archetype.scare_sound = Some(text()?),
+                "radius" => {
+                    let radius = num(0)?;
+                    if !(0.0..=MAX_RADIUS).contains(&radius) {
+                        return Err(err(&format!("radius: debe estar entre 0 y {}", MAX_RADIUS)));
+                    }
+                    archetype.radius = radius;
+                }
+                "size" => archetype.size = num(0)?,
+                "z" => archetype.z_offset = num(0)?,
+                "blend" => {
+                    archetype.blend = values
+                        .first()
+                        .and_then(|v| BlendMode::parse(v))
+                        .ok_or_else(|| err("blend: se esperaba alpha, additive o multiply"))?;
+                    if values.len() > 1 {
+                        archetype.opacity = num(1)?.clamp(0.0, 1.0);
+                    }
+                }
+                "behavior" => {
+                    archetype.behavior = match values.first() {
+                        Some(&"static") => Behavior::Static,
+                        Some(&"creature") => Behavior::Creature,
+                        Some(&"pickup") => {
+                            let item = values.get(1).ok_or_else(|| err("behavior pickup: falta el objeto"))?;
+                            archetype.roles = parse_roles(&values[2..]).map_err(|e| err(&e))?;
+                            Behavior::Pickup(item.to_string())
+                        }
+                        _ => return Err(err("behavior: se esperaba static, creature o pickup")),
+                    }
+                }
+                "map_color" => archetype.map_color = color()?,
+                "effect" => archetype.effect = Some(text()?),
+                _ => return Err(err(&format!("clave desconocida '{}'", key))),
+            }
+        }
+
+        for archetype in &list {
+            if archetype.texture.is_empty() {
+                return Err(format!("{}: el tipo '{}' no tiene textura", path, archetype.name));
+            }
+            let total = archetype.frames * archetype.directions;
+            if let Some((n, _, _)) = archetype.layout.frames.iter().find(|(n, _, _)| *n >= total) {
+                return Err(format!("{}: el tipo '{}' tiene {} frames y define el frame {}", path, archetype.name, total, n));
+            }
+        }
+
+        Ok(Archetypes { list })
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.list.iter().position(|a| a.name == name)
+    }
+
+    pub fn get(&self, id: usize) -> &Archetype {
+        &self.list[id]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Archetype)> {
+        self.list.iter().enumerate()
+    }
+
+    // Objetos (sin repetir) que dan los tipos con esos roles
+    pub fn items_where(&self, filter: impl Fn(&ItemRoles) -> bool) -> Vec<&str> {
+        let mut items: Vec<&str> = Vec::new();
+        for item in self.list.iter().filter(|a| filter(&a.roles)).filter_map(Archetype::item) {
+            if !items.contains(&item) {
+                items.push(item);
+            }
+        }
+        items
+    }
+
+    // Objetos del inventario en el orden del archivo, con el primer tipo que los da (su icono en el HUD)
+    pub fn items(&self) -> Vec<(&str, usize)> {
+        let mut items: Vec<(&str, usize)> = Vec::new();
+        for (id, item) in self.iter().filter_map(|(id, a)| a.item().map(|item| (id, item))) {
+            if !items.iter().any(|(i, _)| *i == item) {
+                items.push((item, id));
+            }
+        }
+        items
+    }
+}
+
+// Opciones tras `behavior pickup objeto`: `counts_for gems|runes` y `opens R|G|B`
+fn parse_roles(values: &[&str]) -> Result<ItemRoles, String> {
+    let mut roles = ItemRoles::default();
+    let mut options = values.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().ok_or_else(|| format!("behavior pickup: falta el valor de '{}'", option))?;
+        match *option {
+            "counts_for" => {
+                roles.counts_for =
+                    Some(Tally::parse(value).ok_or_else(|| "counts_for: se esperaba gems o runes".to_string())?)
+            }
+            "opens" => {
+                let color = value.chars().next().filter(|_| value.len() == 1).and_then(KeyColor::from_glyph);
+                roles.opens = Some(color.ok_or_else(|| "opens: se esperaba R, G o B".to_string())?);
+            }
+            other => return Err(format!("behavior pickup: opción desconocida '{}'", other)),
+        }
+    }
+    Ok(roles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Result<Archetypes, String> {
+        Archetypes::parse("test.def", content)
+    }
+
+    #[test]
+    fn builtin_types_and_roles() {
+        let archetypes = Archetypes::builtin();
+        let creature = archetypes.get(archetypes.find("creature").unwrap());
+        assert_eq!(creature.behavior, Behavior::Creature);
+        assert!(creature.scare_sound.is_some());
+
+        let gem = archetypes.get(archetypes.find("gem").unwrap());
+        assert_eq!(gem.item(), Some("gem"));
+        assert_eq!(gem.roles.counts_for, Some(Tally::Gems));
+        assert_eq!(archetypes.items_where(|r| r.opens == Some(KeyColor::Blue)), vec!["key_blue"]);
+        assert_eq!(archetypes.items_where(|r| r.counts_for == Some(Tally::Runes)), vec!["rune"]);
+    }
+
+    #[test]
+    fn parses_every_key() {
+        let archetypes = parse(
+            "# comentario\n\
+             archetype bat\n\
+             label Murciélago gigante\n\
+             texture bat.png   # hoja\n\
+             frames 2\n\
+             directions 4\n\
+             frame_size 32 32\n\
+             columns 2\n\
+             pivot 16 30\n\
+             frame 7 0 0 16 16 8 16\n\
+             tint 10 20 300\n\
+             clips bat.clips\n\
+             sound bat.mp3\n\
+             scare_sound grito.mp3\n\
+             radius 12\n\
+             size 45\n\
+             z 30\n\
+             blend additive 0.5\n\
+             behavior creature\n\
+             map_color 1 2 3\n\
+             effect sparkles\n",
+        )
+        .unwrap();
+        let bat = archetypes.get(0);
+        assert_eq!(bat.label, "Murciélago gigante");
+        assert_eq!((bat.frames, bat.directions), (2, 4));
+        assert_eq!(bat.layout.frame_size, Some((32, 32)));
+        assert_eq!(bat.layout.frames.len(), 1);
+        assert_eq!(bat.tint, Some(Color::new(10, 20, 255, 255)));
+        assert_eq!(bat.clips_path(), PathBuf::from("bat.clips"));
+        assert_eq!((bat.radius, bat.size, bat.z_offset, bat.opacity), (12.0, 45.0, 30.0, 0.5));
+        assert_eq!(bat.blend, BlendMode::Additive);
+        assert_eq!(bat.effect.as_deref(), Some("sparkles"));
+    }
+
+    #[test]
+    fn clips_default_next_to_the_texture() {
+        let archetypes = parse("archetype a\ntexture assets/a.png\n").unwrap();
+        assert_eq!(archetypes.get(0).clips_path(), PathBuf::from("assets/a.anim"));
+    }
+
+    #[test]
+    fn pickup_roles() {
+        let archetypes = parse(
+            "archetype coin\ntexture c.png\nbehavior pickup coin counts_for gems\n\
+             archetype big_coin\ntexture c.png\nbehavior pickup coin counts_for gems\n\
+             archetype key\ntexture k.png\nbehavior pickup key opens G\n",
+        )
+        .unwrap();
+        assert_eq!(archetypes.items(), vec![("coin", 0), ("key", 2)]);
+        assert_eq!(archetypes.items_where(|r| r.counts_for == Some(Tally::Gems)), vec!["coin"]);
+        assert_eq!(archetypes.get(2).roles.opens, Some(KeyColor::Green));
+    }
+
+    #[test]
+    fn errors_name_file_and_line() {
+        let cases = [
+            ("texture a.png", "se esperaba una línea archetype"),
+            ("archetype a\narchetype a", "repetido"),
+            ("archetype a\nwings 2", "clave desconocida"),
+            ("archetype a\nframes muchos", "valor numérico"),
+            ("archetype a\nframes 0", "entre 1 y 64"),
+            ("archetype a\nframes 1e30", "entre 1 y 64"),
+            ("archetype a\ndirections 100000", "entre 1 y 16"),
+            ("archetype a\nradius 80", "radius"),
+            ("archetype a\nradius -1", "radius"),
+            ("archetype a\nblend glow", "blend"),
+            ("archetype a\nbehavior fly", "behavior"),
+            ("archetype a\nbehavior pickup", "falta el objeto"),
+            ("archetype a\nbehavior pickup x counts_for stars", "counts_for"),
+            ("archetype a\nbehavior pickup x opens Y", "opens"),
+            ("archetype a\nbehavior pickup x opens", "falta el valor"),
+            ("archetype a\nbehavior pickup x glows yes", "opción desconocida"),
+        ];
+        for (content, expected) in cases {
+            let err = parse(content).unwrap_err();
+            let line = content.lines().count();
+            assert!(err.contains(&format!("test.def:{}:", line)) && err.contains(expected), "{}: {}", content, err);
+        }
+    }
+
+    #[test]
+    fn validates_textures_and_frames() {
+        assert!(parse("archetype a\nlabel A\n").unwrap_err().contains("no tiene textura"));
+        let err = parse("archetype a\ntexture a.png\nframes 2\nframe 2 0 0 8 8\n").unwrap_err();
+        assert!(err.contains("define el frame 2"), "{}", err);
+        assert!(Archetypes::load("no-existe.def").is_err());
+    }
+}
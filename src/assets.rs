@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+use std::fs;
+
+pub const MANIFEST_FILE: &str = "assets/manifest.txt";
+
+// assets/manifest.txt tal como estaba al compilar
+const BUILTIN_MANIFEST: &str = include_str!("../assets/manifest.txt");
+
+// Recursos del juego listados en assets/manifest.txt
+pub struct Manifest {
+    pub textures: Vec<(char, String)>, // glifo del laberinto e imagen de la pared
+    pub door_texture: Option<String>,  // se tiñe con el color de cada llave
+    pub sprites: String,               // tipos de sprite, con sus hojas y sonidos de proximidad
+    pub goal_burst: Option<String>,    // tipo de sprite cuya hoja usan las partículas al llegar a la meta
+    pub music: Vec<(String, String)>,  // nombre y archivo
+    pub sounds: Vec<(String, String)>,
+}
+
+impl Manifest {
+    // Formato: una entrada por línea, `clave valores...`. Los comentarios son líneas que empiezan
+    // con `#` (no se cortan a mitad de línea porque `#` también es un glifo de pared).
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(path, &content)
+    }
+
+    // El manifiesto con el que se compiló el juego. Junto con `Archetypes::builtin` reemplaza a los
+    // archivos de texto que falten, así el juego arranca igual; las imágenes y sonidos que falten
+    // se reemplazan al cargarlos y se anotan en el `AssetReport`.
+    pub fn builtin() -> Self {
+        Self::parse(MANIFEST_FILE, BUILTIN_MANIFEST).expect("el manifiesto incluido debe ser válido")
+    }
+
+    fn parse(path: &str, content: &str) -> Result<Self, String> {
+        let mut manifest = Manifest {
+            textures: Vec::new(),
+            door_texture: None,
+            goal_burst: None,
+            sprites: crate::archetypes::ARCHETYPES_FILE.to_string(),
+            music: Vec::new(),
+            sounds: Vec::new(),
+        };
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values: Vec<&str> = line.split_whitespace().collect();
+            let err = |msg: &str| format!("{}:{}: {}", path, i + 1, msg);
+            let file = || match values.as_slice() {
+                [_, file] => Ok(file.to_string()),
+                _ => Err(err(&format!("{}: se esperaba un archivo", values[0]))),
+            };
+            let pair = || match values.as_slice() {
+                [_, name, file] => Ok((name.to_string(), file.to_string())),
+                _ => Err(err(&format!("{}: se esperaba nombre y archivo", values[0]))),
+            };
+
+            match values[0] {
+                "texture" => {
+                    let (glyph, file) = pair()?;
+                    let mut chars = glyph.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => manifest.textures.push((c, file)),
+                        _ => return Err(err("texture: el glifo debe ser un solo carácter")),
+                    }
+                }
+                "door" => manifest.door_texture = Some(file()?),
+                "goal_burst" => match values.as_slice() {
+                    [_, archetype] => manifest.goal_burst = Some(archetype.to_string()),
+                    _ => return Err(err("goal_burst: se esperaba un tipo de sprite")),
+                },
+                "sprites" => manifest.sprites = file()?,
+                "music" => manifest.music.push(pair()?),
+                "sound" => manifest.sounds.push(pair()?),
+                other => return Err(err(&format!("clave desconocida '{}'", other))),
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn music(&self, name: &str) -> Option<&str> {
+        self.music.iter().find(|(n, _)| n == name).map(|(_, file)| file.as_str())
+    }
+}
+
+// Qué se pudo cargar al iniciar; lo que falta se reemplaza (valores incluidos, tablero de ajedrez o
+// silencio) y se informa
+#[derive(Default)]
+pub struct AssetReport {
+    pub loaded: usize,
+    pub failed: Vec<(String, String)>, // archivo y error
+    seen: HashSet<String>,
+}
+
+impl AssetReport {
+    // Anota el resultado de cargar `path`; devuelve el recurso si se pudo cargar.
+    // Cada archivo cuenta una sola vez aunque lo usen varios recursos.
+    pub fn check<T>(&mut self, path: &str, result: Result<T, String>) -> Option<T> {
+        if !self.seen.insert(path.to_string()) {
+            return result.ok();
+        }
+        match result {
+            Ok(asset) => {
+                self.loaded += 1;
+                Some(asset)
+            }
+            Err(e) => {
+                self.failed.push((path.to_string(), e));
+                None
+            }
+        }
+    }
+
+    pub fn log(&self) {
+        if self.failed.is_empty() {
+            log_info!("Recursos: {} cargados", self.loaded);
+            return;
+        }
+        log_warn!("Recursos: {} cargados, {} con error (se usan reemplazos):", self.loaded, self.failed.len());
+        for (path, error) in &self.failed {
+            log_warn!("  {}: {}", path, error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_manifest() {
+        let manifest = Manifest::builtin();
+        assert!(manifest.textures.iter().any(|(c, _)| *c == '#'));
+        assert_eq!(manifest.sprites, crate::archetypes::ARCHETYPES_FILE);
+        assert!(manifest.music("background").is_some());
+        assert!(manifest.music("boss").is_none());
+        assert_eq!(manifest.goal_burst.as_deref(), Some("gem"));
+    }
+
+    #[test]
+    fn hash_is_a_glyph_not_a_comment() {
+        let manifest = Manifest::parse("m.txt", "# paredes\ntexture # hiedra.png\n  # otra\nsound paso paso.mp3\n").unwrap();
+        assert_eq!(manifest.textures, vec![('#', "hiedra.png".to_string())]);
+        assert_eq!(manifest.sounds, vec![("paso".to_string(), "paso.mp3".to_string())]);
+    }
+
+    #[test]
+    fn manifest_errors() {
+        let cases = [
+            ("texture ## a.png", "un solo carácter"),
+            ("texture + ", "nombre y archivo"),
+            ("door", "se esperaba un archivo"),
+            ("music fondo", "nombre y archivo"),
+            ("goal_burst", "tipo de sprite"),
+            ("fonts a.ttf", "clave desconocida"),
+        ];
+        for (line, expected) in cases {
+            let err = Manifest::parse("m.txt", &format!("\n{}", line)).err().unwrap();
+            assert!(err.contains("m.txt:2:") && err.contains(expected), "{}: {}", line, err);
+        }
+        assert!(Manifest::load("no-existe.txt").is_err());
+    }
+
+    #[test]
+    fn report_counts_each_file_once() {
+        let mut report = AssetReport::default();
+        assert_eq!(report.check("a.png", Ok(1)), Some(1));
+        assert_eq!(report.check("a.png", Ok(2)), Some(2));
+        assert_eq!(report.check::<i32>("b.png", Err("no existe".to_string())), None);
+        assert_eq!(report.check::<i32>("b.png", Err("no existe".to_string())), None);
+        assert_eq!(report.loaded, 1);
+        assert_eq!(report.failed, vec![("b.png".to_string(), "no existe".to_string())]);
+    }
+}
@@ -2,76 +2,132 @@ use raylib::audio::RaylibAudio;
 use std::collections::HashMap;
 use raylib::core::audio::{ Sound};
 use raylib::prelude::*;
+use crate::spatial::SpatialIndex;
 use crate::sprites::{self, Sprite};
 
 pub struct AudioSystem<'a> {
-    pub sounds: HashMap<&'static str, Sound>,
+    pub sounds: HashMap<String, Sound>,
     pub audio: &'a mut RaylibAudio,
-    pub background_playing: bool
+    pub background_playing: bool,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    proximity_playing: Vec<String>, // sonidos de proximidad que están sonando
 }
 
 impl<'a> AudioSystem<'a> {
-    pub fn new(audio: &'a mut RaylibAudio) -> Self {
+    pub fn new(audio: &'a mut RaylibAudio, music_volume: f32, sfx_volume: f32) -> Self {
         AudioSystem {
             sounds: HashMap::new(),
             audio,
             background_playing: true,
+            music_volume,
+            sfx_volume,
+            proximity_playing: Vec::new(),
         }
     }
 
-    pub fn load_sound(&mut self, key: &'static str, path: &str) {
-        let sound = Sound::load_sound(path).expect("Error al cargar sonido");
-        self.sounds.insert(key, sound);
+    // Si no se puede cargar, ese efecto queda en silencio
+    pub fn load_sound(&mut self, key: &str, path: &str) -> Result<(), String> {
+        let sound = Sound::load_sound(path)?;
+        self.sounds.insert(key.to_string(), sound);
+        Ok(())
     }
 
-    pub fn play_proximity_sounds(&mut self, player_pos: Vector2, sprites: &[Sprite]) {
+    pub fn play_proximity_sounds(&mut self, player_pos: Vector2, sprites: &[Sprite], spatial: &SpatialIndex) {
         const PROXIMITY_RADIUS: f32 = 200.0;
-        
-        for sprite in sprites {
-            // Correct distance calculation
-            let dx = player_pos.x - sprite.pos.x;
-            let dy = player_pos.y - sprite.pos.y;
-            let distance = (dx * dx + dy * dy).sqrt();
-            
-            if distance < PROXIMITY_RADIUS {
-                let volume = 2.0 - (distance / PROXIMITY_RADIUS).powf(2.0);
-                if let Some(sound) = self.sounds.get_mut(sprite.sound_key) {
-                    self.audio.set_sound_volume(sound, volume);
-                    
-                    if !self.audio.is_sound_playing(sound) {
-                        self.audio.play_sound(sound);
-                    }
-                }
-            } else {
-                if let Some(sound) = self.sounds.get(sprite.sound_key) {
-                    self.audio.stop_sound(sound);
+
+        // Distancia al sprite más cercano de cada sonido: varios sprites con el mismo sonido no se pisan
+        let mut nearest: Vec<(&str, f32)> = Vec::new();
+        for i in spatial.query_radius(player_pos, PROXIMITY_RADIUS) {
+            let sprite = &sprites[i];
+            let distance = (player_pos.x - sprite.pos.x).hypot(player_pos.y - sprite.pos.y);
+            if distance >= PROXIMITY_RADIUS || sprite.sound_key.is_empty() {
+                continue;
+            }
+            match nearest.iter_mut().find(|(key, _)| *key == sprite.sound_key) {
+                Some(entry) => entry.1 = entry.1.min(distance),
+                None => nearest.push((&sprite.sound_key, distance)),
+            }
+        }
+
+        for &(key, distance) in &nearest {
+            let volume = (2.0 - (distance / PROXIMITY_RADIUS).powf(2.0)) * self.sfx_volume;
+            if let Some(sound) = self.sounds.get_mut(key) {
+                self.audio.set_sound_volume(sound, volume);
+
+                if !self.audio.is_sound_playing(sound) {
+                    self.audio.play_sound(sound);
                 }
             }
         }
+
+        // Los que ya no tienen ningún sprite cerca se detienen
+        for key in &self.proximity_playing {
+            if !nearest.iter().any(|(k, _)| *k == key.as_str())
+                && let Some(sound) = self.sounds.get(key)
+            {
+                self.audio.stop_sound(sound);
+            }
+        }
+        self.proximity_playing = nearest.into_iter().map(|(key, _)| key.to_string()).collect();
     }
 
     pub fn update_zone_music(
         &mut self,
         in_special_zone: bool,
-        background_music: &Sound,
-        zone_music: &Sound,
+        background_music: Option<&Sound>,
+        zone_music: Option<&Sound>,
     ) {
         match (self.background_playing, in_special_zone) {
             (true, true) => {
-                self.audio.stop_sound(background_music);
-                self.audio.play_sound(zone_music);
+                self.stop_music(background_music);
+                self.play_music(zone_music);
                 self.background_playing = false;
             }
             (false, false) => {
-                self.audio.stop_sound(zone_music);
-                self.audio.play_sound(background_music);
+                self.stop_music(zone_music);
+                self.play_music(background_music);
                 self.background_playing = true;
             }
             _ => {}
         }
-        
-        // Ensure proper volume when transitioning
-        self.audio.set_sound_volume(background_music, 0.5);
-        self.audio.set_sound_volume(zone_music, 0.5);
+    }
+
+    // La música que no se pudo cargar es `None` y no suena
+    pub fn play_music(&mut self, music: Option<&Sound>) {
+        if let Some(music) = music {
+            self.audio.set_sound_volume(music, self.music_volume);
+            self.audio.play_sound(music);
+        }
+    }
+
+    fn stop_music(&mut self, music: Option<&Sound>) {
+        if let Some(music) = music {
+            self.audio.stop_sound(music);
+        }
+    }
+
+    // Efecto puntual al volumen de efectos
+    pub fn play_effect(&mut self, key: &str) {
+        if let Some(sound) = self.sounds.get(key) {
+            self.audio.set_sound_volume(sound, self.sfx_volume);
+            self.audio.play_sound(sound);
+        }
+    }
+
+    pub fn stop_effects(&mut self) {
+        for sound in self.sounds.values() {
+            self.audio.stop_sound(sound);
+        }
+    }
+
+    // Detiene todos los efectos y vuelve a la música de fondo (reinicio de nivel)
+    pub fn reset(&mut self, background_music: Option<&Sound>, zone_music: Option<&Sound>) {
+        self.stop_effects();
+
+        self.stop_music(zone_music);
+        self.stop_music(background_music);
+        self.play_music(background_music);
+        self.background_playing = true;
     }
 }
\ No newline at end of file
@@ -1,8 +1,15 @@
 use raylib::color::Color;
+use raylib::math::Vector2;
 
+use crate::fog::FogOfWar;
 use crate::framebuffer::Framebuffer;
 use crate::player::{self, Player};
-use crate::maze::Maze;
+use crate::maze::{Maze, cell_at};
+use crate::minimap::MapView;
+
+const RAY_COLOR: Color = Color::new(200, 190, 120, 255);
+const CROSSING_COLOR: Color = Color::new(120, 110, 70, 255);
+const HIT_COLOR: Color = Color::new(255, 90, 60, 255);
 
 pub struct Intersect {
     pub distance: f32,
@@ -17,7 +24,8 @@ pub fn cast_ray(
     player: &Player, 
     block_size: usize, 
     a: f32, 
-    draw: bool
+    draw: Option<&MapView>,
+    mut fog: Option<&mut FogOfWar>
 ) -> Intersect {
     let dx = a.cos();
     let dy = a.sin();
@@ -25,6 +33,11 @@ pub fn cast_ray(
     // Posición actual en coordenadas del mapa
     let mut map_x = (player.pos.x / block_size as f32) as i32;
     let mut map_y = (player.pos.y / block_size as f32) as i32;
+
+    // Las celdas que atraviesa el rayo (y la pared que golpea) quedan descubiertas en el mapa
+    if let Some(fog) = fog.as_deref_mut() {
+        fog.reveal(map_x, map_y);
+    }
     
     // Distancia entre intersecciones consecutivas
     let delta_dist_x = if dx == 0.0 { 1e30 } else { (1.0 / dx).abs() };
@@ -54,9 +67,24 @@ pub fn cast_ray(
     let mut hit = false;
     let mut side = false; // false si es pared NS, true si es pared EW
     let mut wall_type = ' ';
+
+    // Punto del mundo a distancia t (en celdas) a lo largo del rayo
+    let point_at = |t: f32| (
+        player.pos.x + dx * t * block_size as f32,
+        player.pos.y + dy * t * block_size as f32,
+    );
+    let mut last_point = (player.pos.x, player.pos.y);
     
     // DDA - Solo salta de cuadrícula en cuadrícula
     while !hit {
+        // Al dibujar, se traza el tramo hasta el siguiente borde de celda que cruza el rayo
+        if let Some(view) = draw {
+            let crossing = point_at(side_dist_x.min(side_dist_y));
+            plot_segment(framebuffer, view, last_point, crossing, RAY_COLOR);
+            plot_point(framebuffer, view, crossing, 0, CROSSING_COLOR);
+            last_point = crossing;
+        }
+
         if side_dist_x < side_dist_y {
             side_dist_x += delta_dist_x;
             map_x += step_x;
@@ -67,16 +95,21 @@ pub fn cast_ray(
             side = true;
         }
         
-        // Verificar límites y colisión
-        if map_y >= 0 && map_y < maze.len() as i32 && 
-           map_x >= 0 && map_x < maze[0].len() as i32 {
-            wall_type = maze[map_y as usize][map_x as usize];
-            if wall_type != ' ' {
+        if let Some(fog) = fog.as_deref_mut() {
+            fog.reveal(map_x, map_y);
+        }
+
+        // Verificar límites y colisión (las filas pueden tener largos distintos)
+        match cell_at(maze, map_x, map_y) {
+            Some(' ') => {}
+            Some(cell) => {
                 hit = true;
+                wall_type = cell;
+            }
+            None => {
+                hit = true;
+                wall_type = '#'; // Pared por defecto fuera de límites
             }
-        } else {
-            hit = true;
-            wall_type = '#'; // Pared por defecto fuera de límites
         }
     }
     
@@ -88,6 +121,12 @@ pub fn cast_ray(
     };
     
     let distance = perp_wall_dist * block_size as f32;
+
+    if let Some(view) = draw {
+        let hit_point = point_at(perp_wall_dist);
+        plot_segment(framebuffer, view, last_point, hit_point, RAY_COLOR);
+        plot_point(framebuffer, view, hit_point, 1, HIT_COLOR);
+    }
     
     // Calcular coordenada de textura
     let wall_x = if !side {
@@ -106,3 +145,70 @@ pub fn cast_ray(
         side
     }
 }
+
+fn plot_segment(framebuffer: &mut Framebuffer, view: &MapView, from: (f32, f32), to: (f32, f32), color: Color) {
+    let a = view.world_to_map(Vector2::new(from.0, from.1));
+    let b = view.world_to_map(Vector2::new(to.0, to.1));
+    framebuffer.set_current_color(color);
+    framebuffer.draw_line(a.x as i32, a.y as i32, b.x as i32, b.y as i32);
+}
+
+fn plot_point(framebuffer: &mut Framebuffer, view: &MapView, point: (f32, f32), half_size: i32, color: Color) {
+    let p = view.world_to_map(Vector2::new(point.0, point.1));
+    framebuffer.set_current_color(color);
+    framebuffer.fill_square(p.x as i32, p.y as i32, half_size);
+}
+
+// Visibilidad entre dos puntos del mundo: recorre las celdas del segmento (DDA) buscando paredes
+pub fn line_of_sight(maze: &Maze, block_size: usize, from: Vector2, to: Vector2) -> bool {
+    let bs = block_size as f32;
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = dx.hypot(dy);
+    if length < 1e-3 {
+        return true;
+    }
+    let (dir_x, dir_y) = (dx / length, dy / length);
+
+    let mut map_x = (from.x / bs).floor() as i32;
+    let mut map_y = (from.y / bs).floor() as i32;
+    let target_x = (to.x / bs).floor() as i32;
+    let target_y = (to.y / bs).floor() as i32;
+
+    // Distancias en unidades del mundo, igual que el DDA de cast_ray
+    let delta_x = if dir_x == 0.0 { f32::INFINITY } else { (bs / dir_x).abs() };
+    let delta_y = if dir_y == 0.0 { f32::INFINITY } else { (bs / dir_y).abs() };
+    let step_x = if dir_x < 0.0 { -1 } else { 1 };
+    let step_y = if dir_y < 0.0 { -1 } else { 1 };
+    let mut side_x = if dir_x < 0.0 {
+        (from.x - map_x as f32 * bs) / -dir_x
+    } else {
+        ((map_x + 1) as f32 * bs - from.x) / dir_x.max(1e-9)
+    };
+    let mut side_y = if dir_y < 0.0 {
+        (from.y - map_y as f32 * bs) / -dir_y
+    } else {
+        ((map_y + 1) as f32 * bs - from.y) / dir_y.max(1e-9)
+    };
+
+    while (map_x, map_y) != (target_x, target_y) {
+        if side_x < side_y {
+            if side_x > length {
+                break;
+            }
+            side_x += delta_x;
+            map_x += step_x;
+        } else {
+            if side_y > length {
+                break;
+            }
+            side_y += delta_y;
+            map_y += step_y;
+        }
+
+        if cell_at(maze, map_x, map_y) != Some(' ') {
+            return false;
+        }
+    }
+
+    true
+}
@@ -0,0 +1,322 @@
+use std::f32::consts::PI;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::logger::LogLevel;
+use crate::minimap::MinimapMode;
+
+pub const CONFIG_FILE: &str = "config.txt";
+
+// Tope de rayos del minimapa, tanto en la configuración como al subirlos con la tecla
+pub const MAX_MINIMAP_RAYS: usize = 64;
+
+pub const USAGE: &str = "Uso: Proyecto1_Raycasting [opciones]
+  --config <archivo>        archivo de configuración (por defecto config.txt)
+  --level <archivo>         laberinto a cargar
+  --width <px>              ancho de la ventana
+  --height <px>             alto de la ventana
+  --internal-res <WxH>      resolución interna de render, p. ej. 500x400
+  --fov <grados>            campo de visión
+  --mute                    silenciar todo el audio
+  --seed <n>                semilla para los elementos aleatorios
+  --debug                   iniciar con el overlay de depuración visible
+  --log-level <nivel>       error, warn, info o debug
+  --log-file <archivo>      escribir el log en un archivo en vez de stderr
+  --help                    mostrar esta ayuda";
+
+pub struct Config {
+    pub window_width: i32,
+    pub window_height: i32,
+    pub internal_width: u32,
+    pub internal_height: u32,
+    pub block_size: usize,
+    pub fov: f32, // radianes
+    pub level: String,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub mute: bool,
+    pub seed: u64,
+    pub debug_overlay: bool,
+    pub log_level: LogLevel,
+    pub log_file: Option<String>,
+    pub minimap_mode: MinimapMode,
+    pub minimap_rotate: bool,
+    pub minimap_rays: usize,
+    pub pixel_depth: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Config {
+            window_width: 1000,
+            window_height: 800,
+            internal_width: 500,
+            internal_height: 400,
+            block_size: 80,
+            fov: PI / 3.0,
+            level: "maze.txt".to_string(),
+            music_volume: 0.5,
+            sfx_volume: 1.0,
+            mute: false,
+            seed,
+            debug_overlay: false,
+            log_level: LogLevel::Info,
+            log_file: None,
+            minimap_mode: MinimapMode::Follow,
+            minimap_rotate: false,
+            minimap_rays: 3,
+            pixel_depth: false,
+        }
+    }
+}
+
+impl Config {
+    // Valores por defecto <- archivo de configuración <- argumentos de línea de comandos
+    pub fn from_args(args: &[String]) -> Result<Config, String> {
+        let mut config = Config::default();
+
+        let config_path = match args.iter().position(|a| a == "--config") {
+            Some(i) => Some(args.get(i + 1).ok_or("--config: falta el archivo")?.as_str()),
+            None => None,
+        };
+
+        match config_path {
+            Some(path) => config.load_file(path)?,
+            None if Path::new(CONFIG_FILE).exists() => config.load_file(CONFIG_FILE)?,
+            None => {}
+        }
+
+        config.apply_args(args)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Formato `clave = valor`, con comentarios `#`
+    fn load_file(&mut self, path: &str) -> Result<(), String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("{}:{}: se esperaba `clave = valor`", path, i + 1))?;
+
+            self.set(key.trim(), value.trim())
+                .map_err(|e| format!("{}:{}: {}", path, i + 1, e))?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_args(&mut self, args: &[String]) -> Result<(), String> {
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().ok_or(format!("{}: falta el valor", arg));
+
+            match arg.as_str() {
+                "--config" => {
+                    value()?;
+                }
+                "--level" => self.set("level", value()?)?,
+                "--width" => self.set("window_width", value()?)?,
+                "--height" => self.set("window_height", value()?)?,
+                "--fov" => self.set("fov", value()?)?,
+                "--seed" => self.set("seed", value()?)?,
+                "--internal-res" => {
+                    let res = value()?;
+                    let (w, h) = res
+                        .split_once('x')
+                        .ok_or(format!("--internal-res: se esperaba ANCHOxALTO, se recibió '{}'", res))?;
+                    self.set("internal_width", w)?;
+                    self.set("internal_height", h)?;
+                }
+                "--log-level" => self.set("log_level", value()?)?,
+                "--log-file" => self.set("log_file", value()?)?,
+                "--mute" => self.mute = true,
+                "--debug" => self.debug_overlay = true,
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                other => return Err(format!("opción desconocida '{}'\n{}", other, USAGE)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(key: &str, value: &str, expected: &str) -> Result<T, String> {
+            value
+                .parse::<T>()
+                .map_err(|_| format!("{}: se esperaba {}, se recibió '{}'", key, expected, value))
+        }
+
+        match key {
+            "window_width" => self.window_width = parse(key, value, "un entero")?,
+            "window_height" => self.window_height = parse(key, value, "un entero")?,
+            "internal_width" => self.internal_width = parse(key, value, "un entero")?,
+            "internal_height" => self.internal_height = parse(key, value, "un entero")?,
+            "block_size" => self.block_size = parse(key, value, "un entero")?,
+            "fov" => self.fov = parse::<f32>(key, value, "grados")?.to_radians(),
+            "level" => self.level = value.to_string(),
+            "music_volume" => self.music_volume = parse(key, value, "un número entre 0 y 1")?,
+            "sfx_volume" => self.sfx_volume = parse(key, value, "un número entre 0 y 1")?,
+            "mute" => self.mute = parse(key, value, "true o false")?,
+            "seed" => self.seed = parse(key, value, "un entero positivo")?,
+            "debug_overlay" => self.debug_overlay = parse(key, value, "true o false")?,
+            "log_level" => {
+                self.log_level = LogLevel::parse(value)
+                    .ok_or(format!("{}: se esperaba error, warn, info o debug, se recibió '{}'", key, value))?
+            }
+            "log_file" => self.log_file = Some(value.to_string()),
+            "minimap_mode" => {
+                self.minimap_mode = match value {
+                    "fit" => MinimapMode::Fit,
+                    "follow" => MinimapMode::Follow,
+                    _ => return Err(format!("{}: se esperaba fit o follow, se recibió '{}'", key, value)),
+                }
+            }
+            "minimap_rotate" => self.minimap_rotate = parse(key, value, "true o false")?,
+            "minimap_rays" => self.minimap_rays = parse(key, value, "un entero")?,
+            "pixel_depth" => self.pixel_depth = parse(key, value, "true o false")?,
+            _ => return Err(format!("clave desconocida '{}'", key)),
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.window_width < 320 || self.window_height < 240 {
+            return Err(format!(
+                "la ventana debe medir al menos 320x240 (se pidió {}x{})",
+                self.window_width, self.window_height
+            ));
+        }
+        if self.internal_width < 64 || self.internal_height < 48 {
+            return Err(format!(
+                "la resolución interna debe ser al menos 64x48 (se pidió {}x{})",
+                self.internal_width, self.internal_height
+            ));
+        }
+        if self.internal_width as i32 > self.window_width || self.internal_height as i32 > self.window_height {
+            return Err(format!(
+                "la resolución interna {}x{} no puede superar la ventana {}x{}",
+                self.internal_width, self.internal_height, self.window_width, self.window_height
+            ));
+        }
+        if self.block_size < 8 {
+            return Err(format!("block_size debe ser al menos 8 (se pidió {})", self.block_size));
+        }
+        let fov_deg = self.fov.to_degrees();
+        if !(30.0..=150.0).contains(&fov_deg) {
+            return Err(format!("fov debe estar entre 30 y 150 grados (se pidió {:.0})", fov_deg));
+        }
+        for (name, volume) in [("music_volume", self.music_volume), ("sfx_volume", self.sfx_volume)] {
+            if !(0.0..=1.0).contains(&volume) {
+                return Err(format!("{} debe estar entre 0 y 1 (se pidió {})", name, volume));
+            }
+        }
+        if self.minimap_rays > MAX_MINIMAP_RAYS {
+            return Err(format!(
+                "minimap_rays debe estar entre 0 y {} (se pidió {})",
+                MAX_MINIMAP_RAYS, self.minimap_rays
+            ));
+        }
+        if !Path::new(&self.level).is_file() {
+            return Err(format!("no existe el nivel '{}'", self.level));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{TempFile, SMALL_MAZE};
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    // Configuración en un archivo temporal que apunta a un nivel que existe
+    fn load(name: &str, content: &str, extra: &[&str]) -> Result<Config, String> {
+        let level = TempFile::new(&format!("{}.txt", name), SMALL_MAZE);
+        let file = TempFile::new(&format!("{}.cfg", name), &format!("level = {}\n{}", level.path(), content));
+        let mut list = vec!["--config", file.path()];
+        list.extend_from_slice(extra);
+        Config::from_args(&args(&list))
+    }
+
+    fn error(name: &str, content: &str, extra: &[&str]) -> String {
+        load(name, content, extra).err().expect("se esperaba un error")
+    }
+
+    #[test]
+    fn file_values_and_arguments() {
+        let config = load("cfg_ok", "block_size = 64  # celdas\nfov = 90\nminimap_rays = 5\n", &["--seed", "7", "--mute"])
+            .unwrap();
+        assert_eq!(config.block_size, 64);
+        assert!((config.fov.to_degrees() - 90.0).abs() < 1e-3);
+        assert_eq!(config.minimap_rays, 5);
+        assert_eq!(config.seed, 7);
+        assert!(config.mute);
+    }
+
+    #[test]
+    fn arguments_override_the_file() {
+        let config = load("cfg_override", "window_width = 800\n", &["--width", "1024", "--internal-res", "320x200"]).unwrap();
+        assert_eq!(config.window_width, 1024);
+        assert_eq!((config.internal_width, config.internal_height), (320, 200));
+    }
+
+    #[test]
+    fn malformed_lines_report_file_and_line() {
+        let err = error("cfg_syntax", "\nblock_size 64\n", &[]);
+        assert!(err.contains("cfg_syntax.cfg:3"), "{}", err);
+
+        let err = error("cfg_key", "colour = red\n", &[]);
+        assert!(err.contains("clave desconocida 'colour'"), "{}", err);
+
+        let err = error("cfg_number", "block_size = grande\n", &[]);
+        assert!(err.contains("block_size: se esperaba un entero"), "{}", err);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let cases = [
+            ("block_size = 4", "block_size"),
+            ("fov = 10", "fov"),
+            ("music_volume = 2", "music_volume"),
+            ("window_width = 100", "la ventana"),
+            ("internal_width = 2000", "no puede superar"),
+            ("minimap_rays = 65", "minimap_rays"),
+            ("minimap_mode = zoom", "minimap_mode"),
+            ("log_level = verbose", "log_level"),
+        ];
+        for (i, (line, expected)) in cases.iter().enumerate() {
+            let err = error(&format!("cfg_invalid_{}", i), line, &[]);
+            assert!(err.contains(expected), "{}: {}", line, err);
+        }
+        assert!(load("cfg_max_rays", &format!("minimap_rays = {}", MAX_MINIMAP_RAYS), &[]).is_ok());
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(Config::from_args(&args(&["--config"])).is_err());
+        assert!(error("cfg_arg_unknown", "", &["--volume", "3"]).contains("opción desconocida"));
+        assert!(error("cfg_arg_value", "", &["--width"]).contains("falta el valor"));
+        assert!(error("cfg_arg_res", "", &["--internal-res", "320"]).contains("ANCHOxALTO"));
+        assert!(error("cfg_no_level", "level = no-existe.txt\n", &[]).contains("no existe el nivel"));
+        assert!(Config::from_args(&args(&["--config", "no-existe.cfg"])).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use raylib::prelude::*;
+use std::time::Duration;
+
+use crate::maze::Maze;
+use crate::player::Player;
+
+// Tiempos por etapa del frame en milisegundos, suavizados
+#[derive(Default)]
+pub struct FrameTimings {
+    pub raycast: f32,
+    pub sprites: f32,
+    pub minimap: f32,
+    pub present: f32,
+}
+
+pub enum Stage {
+    Raycast,
+    Sprites,
+    Minimap,
+    Present,
+}
+
+pub struct DebugOverlay {
+    pub visible: bool,
+    pub timings: FrameTimings,
+    pub particles: usize, // vivas en el último frame
+}
+
+impl DebugOverlay {
+    pub fn new(visible: bool) -> Self {
+        DebugOverlay { visible, timings: FrameTimings::default(), particles: 0 }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn record(&mut self, stage: Stage, duration: Duration) {
+        const SMOOTHING: f32 = 0.1;
+
+        let ms = duration.as_secs_f32() * 1000.0;
+        let slot = match stage {
+            Stage::Raycast => &mut self.timings.raycast,
+            Stage::Sprites => &mut self.timings.sprites,
+            Stage::Minimap => &mut self.timings.minimap,
+            Stage::Present => &mut self.timings.present,
+        };
+        *slot += (ms - *slot) * SMOOTHING;
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut RaylibDrawHandle,
+        player: &Player,
+        maze: &Maze,
+        block_size: usize,
+        zone: &str,
+        fps: u32,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let cell_x = (player.pos.x / block_size as f32) as usize;
+        let cell_y = (player.pos.y / block_size as f32) as usize;
+        let glyph = maze.get(cell_y).and_then(|row| row.get(cell_x)).copied().unwrap_or(' ');
+        let angle = player.a.to_degrees().rem_euclid(360.0);
+        let t = &self.timings;
+
+        let lines = [
+            format!("Posición: {:.1}, {:.1}", player.pos.x, player.pos.y),
+            format!("Celda: {}, {} ('{}')", cell_x, cell_y, glyph),
+            format!("Ángulo: {:.1}°", angle),
+            format!("Zona: {}", zone),
+            format!("FPS: {}", fps),
+            format!("Partículas: {}", self.particles),
+            format!("Raycast: {:.2} ms", t.raycast),
+            format!("Sprites: {:.2} ms", t.sprites),
+            format!("Minimapa: {:.2} ms", t.minimap),
+            format!("Presentar: {:.2} ms", t.present),
+        ];
+
+        let x = 10;
+        let y = 65;
+        let line_height = 18;
+        d.draw_rectangle(x - 5, y - 5, 230, lines.len() as i32 * line_height + 10, Color::new(0, 0, 0, 180));
+
+        for (i, line) in lines.iter().enumerate() {
+            d.draw_text(line, x, y + i as i32 * line_height, 16, Color::LIME);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::player::Player;
+use crate::textures::TextureManager;
+use crate::WALL_HEIGHT;
+
+// Separación de la pared para que el decal quede delante de la columna del muro
+const WALL_GAP: f32 = 0.5;
+
+// Cara de una celda de pared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Face {
+    pub fn parse(s: &str) -> Option<Face> {
+        match s {
+            "n" => Some(Face::North),
+            "s" => Some(Face::South),
+            "e" => Some(Face::East),
+            "w" => Some(Face::West),
+            _ => None,
+        }
+    }
+}
+
+// Dónde está el decal en el plano del piso
+#[derive(Debug, Clone, Copy)]
+pub enum Placement {
+    // Sobre la cara `face` de la celda de pared `cell`; `u0..u1` es el tramo de la cara (0..1)
+    Wall { cell: (usize, usize), face: Face, u0: f32, u1: f32 },
+    // Segmento libre entre dos puntos del mundo
+    Free { a: Vector2, b: Vector2 },
+}
+
+// Rectángulo plano y vertical fijo en el mundo (no mira a la cámara): carteles, enredaderas, manchas.
+// Va de `z0` a `z1` en altura.
+pub struct Decal {
+    pub placement: Placement,
+    pub z0: f32,
+    pub z1: f32,
+    pub texture: String, // ruta de la imagen
+    pub blend: BlendMode,
+    pub opacity: f32,
+}
+
+impl Decal {
+    pub fn new(placement: Placement, texture: &str) -> Self {
+        Decal {
+            placement,
+            z0: 0.0,
+            z1: WALL_HEIGHT,
+            texture: texture.to_string(),
+            blend: BlendMode::Alpha,
+            opacity: 1.0,
+        }
+    }
+
+    // Extremos en el mundo, de izquierda a derecha vistos desde el frente
+    pub fn segment(&self, block_size: usize) -> (Vector2, Vector2) {
+        match self.placement {
+            Placement::Free { a, b } => (a, b),
+            Placement::Wall { cell, face, u0, u1 } => {
+                let bs = block_size as f32;
+                let (x0, y0) = (cell.0 as f32 * bs, cell.1 as f32 * bs);
+                let (x1, y1) = (x0 + bs, y0 + bs);
+
+                let (start, end) = match face {
+                    Face::North => (Vector2::new(x1, y0 - WALL_GAP), Vector2::new(x0, y0 - WALL_GAP)),
+                    Face::South => (Vector2::new(x0, y1 + WALL_GAP), Vector2::new(x1, y1 + WALL_GAP)),
+                    Face::East => (Vector2::new(x1 + WALL_GAP, y1), Vector2::new(x1 + WALL_GAP, y0)),
+                    Face::West => (Vector2::new(x0 - WALL_GAP, y0), Vector2::new(x0 - WALL_GAP, y1)),
+                };
+                let lerp = |t: f32| Vector2::new(start.x + (end.x - start.x) * t, start.y + (end.y - start.y) * t);
+                (lerp(u0), lerp(u1))
+            }
+        }
+    }
+
+    // Intersección del rayo (origen, dirección) con el segmento: (distancia a lo largo del rayo, u en 0..1)
+    fn intersect(&self, (a, b): (Vector2, Vector2), origin: Vector2, dir: Vector2) -> Option<(f32, f32)> {
+        let edge = Vector2::new(b.x - a.x, b.y - a.y);
+        let denom = dir.x * edge.y - dir.y * edge.x;
+        if denom.abs() < 1e-6 {
+            return None; // paralelo al rayo
+        }
+
+        let to_a = Vector2::new(a.x - origin.x, a.y - origin.y);
+        let t = (to_a.x * edge.y - to_a.y * edge.x) / denom;
+        let u = (to_a.x * dir.y - to_a.y * dir.x) / denom;
+
+        if t > 0.0 && (0.0..=1.0).contains(&u) { Some((t, u)) } else { None }
+    }
+}
+
+// Se dibuja por columnas con los mismos rayos que las paredes: el `u` de la intersección da la
+// coordenada de textura exacta (perspectiva correcta) y la profundidad se compara con la del muro
+pub fn render_decals(
+    framebuffer: &mut Framebuffer,
+    player: &Player,
+    decals: &[Decal],
+    block_size: usize,
+    texture_cache: &TextureManager,
+) {
+    if decals.is_empty() {
+        return;
+    }
+    let segments: Vec<(Vector2, Vector2)> = decals.iter().map(|d| d.segment(block_size)).collect();
+
+    let num_rays = framebuffer.width;
+    let hh = framebuffer.height as f32 / 2.0;
+    let fov_step = player.fov / num_rays as f32;
+
+    for i in 0..num_rays {
+        let angle_diff = -player.fov / 2.0 + i as f32 * fov_step;
+        let a = player.a + angle_diff;
+        let dir = Vector2::new(a.cos(), a.sin());
+
+        // Los que tapa la pared se descartan; el resto se pinta del más lejano al más cercano
+        let mut hits: Vec<(f32, f32, &Decal)> = decals
+            .iter()
+            .zip(&segments)
+            .filter_map(|(decal, &segment)| {
+                decal.intersect(segment, player.pos, dir).map(|(t, u)| (t * angle_diff.cos(), u, decal))
+            })
+            .filter(|(depth, _, _)| framebuffer.column_visible(i, *depth))
+            .collect();
+        hits.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (depth, u, decal) in hits {
+            let Some((tex_width, tex_height)) = texture_cache.get_decal_size(&decal.texture) else { continue };
+
+            let top = hh - (decal.z1 - WALL_HEIGHT / 2.0) * hh / depth;
+            let bottom = hh - (decal.z0 - WALL_HEIGHT / 2.0) * hh / depth;
+            let tx = ((u * tex_width as f32) as u32).min(tex_width - 1);
+
+            let start_y = top.max(0.0) as u32;
+            let end_y = bottom.min(framebuffer.height as f32).max(0.0) as u32;
+            for y in start_y..end_y {
+                let ty = (((y as f32 - top) / (bottom - top)) * tex_height as f32) as u32;
+                let color = texture_cache.get_decal_pixel_color(&decal.texture, tx, ty.min(tex_height - 1));
+                framebuffer.blend_pixel_depth(i, y, color, decal.blend, decal.opacity, depth);
+            }
+        }
+    }
+}
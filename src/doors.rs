@@ -0,0 +1,55 @@
+use raylib::prelude::*;
+
+use crate::pathfinding::Cell;
+
+// Color de una llave y de las puertas que abre. En el laberinto las puertas son 'R', 'G' y 'B'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyColor {
+    Red,
+    Green,
+    Blue,
+}
+
+impl KeyColor {
+    pub const ALL: [KeyColor; 3] = [KeyColor::Red, KeyColor::Green, KeyColor::Blue];
+
+    pub fn from_glyph(c: char) -> Option<KeyColor> {
+        match c {
+            'R' => Some(KeyColor::Red),
+            'G' => Some(KeyColor::Green),
+            'B' => Some(KeyColor::Blue),
+            _ => None,
+        }
+    }
+
+    pub fn glyph(self) -> char {
+        match self {
+            KeyColor::Red => 'R',
+            KeyColor::Green => 'G',
+            KeyColor::Blue => 'B',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyColor::Red => "roja",
+            KeyColor::Green => "verde",
+            KeyColor::Blue => "azul",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            KeyColor::Red => Color::new(200, 40, 40, 255),
+            KeyColor::Green => Color::new(40, 180, 60, 255),
+            KeyColor::Blue => Color::new(50, 90, 220, 255),
+        }
+    }
+}
+
+// Puerta cerrada con llave; al abrirse su celda queda vacía en el laberinto
+pub struct Door {
+    pub cell: Cell,
+    pub color: KeyColor,
+    pub open: bool,
+}
@@ -0,0 +1,80 @@
+use crate::maze::Maze;
+
+// Celdas del laberinto que el jugador ya vio (marcadas por los rayos de render_3D)
+pub struct FogOfWar {
+    pub enabled: bool,
+    pub width: usize,
+    pub height: usize,
+    seen: Vec<bool>,
+    visible: Vec<bool>, // tocadas por los rayos en el frame actual
+}
+
+impl FogOfWar {
+    pub fn new(maze: &Maze, enabled: bool) -> Self {
+        let width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+        let height = maze.len();
+
+        FogOfWar {
+            enabled,
+            width,
+            height,
+            seen: vec![false; width * height],
+            visible: vec![false; width * height],
+        }
+    }
+
+    // Antes de lanzar los rayos de un frame
+    pub fn begin_frame(&mut self) {
+        self.visible.fill(false);
+    }
+
+    pub fn reveal(&mut self, x: i32, y: i32) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            let index = y as usize * self.width + x as usize;
+            self.seen[index] = true;
+            self.visible[index] = true;
+        }
+    }
+
+    // Vista en este frame (independiente de si la niebla está activa)
+    pub fn is_visible(&self, x: i32, y: i32) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && self.visible[y as usize * self.width + x as usize]
+    }
+
+    // Con la niebla desactivada todo se considera descubierto
+    pub fn is_revealed(&self, x: i32, y: i32) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && self.seen[y as usize * self.width + x as usize]
+    }
+
+    // Una fila por línea, '1' descubierta y '0' sin descubrir
+    pub fn to_rows(&self) -> Vec<String> {
+        self.seen
+            .chunks(self.width.max(1))
+            .map(|row| row.iter().map(|&s| if s { '1' } else { '0' }).collect())
+            .collect()
+    }
+
+    pub fn load_rows(&mut self, rows: &[String]) -> Result<(), String> {
+        if rows.len() != self.height || rows.iter().any(|r| r.chars().count() != self.width) {
+            return Err(format!("el mapa explorado no mide {}x{}", self.width, self.height));
+        }
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                self.seen[y * self.width + x] = c == '1';
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,24 @@
 use raylib::prelude::*;
 
+// Cómo se combina un color con lo que ya hay en el buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Alpha,    // mezcla según la transparencia
+    Additive, // suma: brillos y fantasmas luminosos
+    Multiply, // oscurece: sombras, manchas
+}
+
+impl BlendMode {
+    pub fn parse(s: &str) -> Option<BlendMode> {
+        match s {
+            "alpha" => Some(BlendMode::Alpha),
+            "additive" => Some(BlendMode::Additive),
+            "multiply" => Some(BlendMode::Multiply),
+            _ => None,
+        }
+    }
+}
+
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
@@ -7,7 +26,8 @@ pub struct Framebuffer {
     pub background_color: Color,
     current_color: Color,
     pub light_buffer: Vec<f32>,
-    pub depth_buffer: Vec<f32>, 
+    pub depth_buffer: Vec<f32>, // una profundidad por columna (la de la pared)
+    pixel_depth: Option<Vec<f32>>, // opcional: una por píxel
 }
 
 impl Framebuffer {
@@ -23,6 +43,42 @@ impl Framebuffer {
             current_color: Color::WHITE,
             light_buffer: vec![0.0; buffer_size],
             depth_buffer: vec![f32::MAX; width as usize], 
+            pixel_depth: None,
+        }
+    }
+
+    // Activa o desactiva el buffer de profundidad por píxel. Sin él las pruebas usan la profundidad de la columna.
+    pub fn set_pixel_depth(&mut self, enabled: bool) {
+        self.pixel_depth = if enabled { Some(vec![f32::MAX; (self.width * self.height) as usize]) } else { None };
+    }
+
+    // true si algo a `depth` en (x, y) queda delante de lo ya dibujado
+    #[inline(always)]
+    pub fn depth_test(&self, x: u32, y: u32, depth: f32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        match &self.pixel_depth {
+            Some(buffer) => depth < buffer[(y * self.width + x) as usize],
+            None => depth < self.depth_buffer[x as usize],
+        }
+    }
+
+    // Descarte por columna antes de recorrerla: sin buffer por píxel compara con la pared; con él no descarta
+    // nada y cada píxel pasa por `depth_test` (lo que está delante puede ser un sprite o un decal, no la pared)
+    #[inline(always)]
+    pub fn column_visible(&self, x: u32, depth: f32) -> bool {
+        self.pixel_depth.is_some() || (x < self.width && depth < self.depth_buffer[x as usize])
+    }
+
+    // Registra la profundidad de un píxel opaco (sin buffer por píxel no hace nada)
+    #[inline(always)]
+    pub fn write_depth(&mut self, x: u32, y: u32, depth: f32) {
+        if x < self.width
+            && y < self.height
+            && let Some(buffer) = &mut self.pixel_depth
+        {
+            buffer[(y * self.width + x) as usize] = depth;
         }
     }
 
@@ -30,6 +86,9 @@ impl Framebuffer {
         // Optimización: usar fill es mucho más rápido
         self.color_buffer.fill(self.background_color);
         self.depth_buffer.fill(f32::MAX);
+        if let Some(buffer) = &mut self.pixel_depth {
+            buffer.fill(f32::MAX);
+        }
     }
 
     #[inline(always)]
@@ -42,6 +101,46 @@ impl Framebuffer {
         }
     }
 
+    // Combina `color` con el píxel existente; `opacity` (0..1) multiplica el alfa del color
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Color, mode: BlendMode, opacity: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let alpha = color.a as f32 / 255.0 * opacity;
+        if alpha <= 0.0 {
+            return;
+        }
+
+        let index = (y * self.width + x) as usize;
+        if mode == BlendMode::Alpha && alpha >= 1.0 {
+            self.color_buffer[index] = color;
+            return;
+        }
+
+        let dst = self.color_buffer[index];
+        let mix = |s: u8, d: u8| -> u8 {
+            let (s, d) = (s as f32, d as f32);
+            let out = match mode {
+                BlendMode::Alpha => s * alpha + d * (1.0 - alpha),
+                BlendMode::Additive => d + s * alpha,
+                BlendMode::Multiply => d * (1.0 - alpha + alpha * s / 255.0),
+            };
+            out.min(255.0) as u8
+        };
+        self.color_buffer[index] = Color::new(mix(color.r, dst.r), mix(color.g, dst.g), mix(color.b, dst.b), 255);
+    }
+
+    // blend_pixel con prueba de profundidad; los píxeles opacos además escriben su profundidad
+    pub fn blend_pixel_depth(&mut self, x: u32, y: u32, color: Color, mode: BlendMode, opacity: f32, depth: f32) {
+        if !self.depth_test(x, y, depth) {
+            return;
+        }
+        self.blend_pixel(x, y, color, mode, opacity);
+        if mode == BlendMode::Alpha && color.a == 255 && opacity >= 1.0 {
+            self.write_depth(x, y, depth);
+        }
+    }
+
     pub fn set_pixel(&mut self, x: u32, y: u32) {
         if x < self.width && y < self.height {
             let index = (y * self.width + x) as usize;
@@ -51,6 +150,45 @@ impl Framebuffer {
         }
     }
 
+    // Línea con el color actual (Bresenham); recorta lo que cae fuera del buffer
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+
+        loop {
+            if x >= 0 && y >= 0 {
+                self.set_pixel(x as u32, y as u32);
+            }
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    // Cuadrado relleno centrado en (x, y) con el color actual
+    pub fn fill_square(&mut self, x: i32, y: i32, half_size: i32) {
+        for py in (y - half_size)..=(y + half_size) {
+            for px in (x - half_size)..=(x + half_size) {
+                if px >= 0 && py >= 0 {
+                    self.set_pixel(px as u32, py as u32);
+                }
+            }
+        }
+    }
+
     pub fn set_background_color(&mut self, color: Color) {
         self.background_color = color;
     }
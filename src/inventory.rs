@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use crate::archetypes::{Archetypes, MAX_RADIUS};
+use crate::doors::KeyColor;
+use crate::objectives::Tally;
+use crate::player::{Player, PLAYER_RADIUS};
+use crate::spatial::SpatialIndex;
+use crate::sprites::Sprite;
+
+// Cantidad de cada objeto, por el nombre que le da `behavior pickup` en assets/sprites.def
+#[derive(Debug, Default, Clone)]
+pub struct Inventory {
+    pub counts: HashMap<String, u32>,
+}
+
+impl Inventory {
+    pub fn add(&mut self, item: &str) {
+        *self.counts.entry(item.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn count(&self, item: &str) -> u32 {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    // Alguno de los objetos que abren puertas de ese color (`opens` en assets/sprites.def)
+    pub fn has_key(&self, color: KeyColor, archetypes: &Archetypes) -> bool {
+        archetypes.items_where(|roles| roles.opens == Some(color)).iter().any(|item| self.count(item) > 0)
+    }
+
+    // Cuántos de los objetos que suman a ese objetivo hay (`counts_for` en assets/sprites.def)
+    pub fn tally(&self, tally: Tally, archetypes: &Archetypes) -> u32 {
+        archetypes.items_where(|roles| roles.counts_for == Some(tally)).iter().map(|item| self.count(item)).sum()
+    }
+}
+
+// Recoge los objetos al alcance del jugador; devuelve los tipos recogidos en este frame
+pub fn collect_items(
+    sprites: &mut [Sprite],
+    player: &Player,
+    spatial: &SpatialIndex,
+    inventory: &mut Inventory,
+) -> Vec<usize> {
+    let mut collected = Vec::new();
+
+    for i in spatial.query_radius(player.pos, MAX_RADIUS + PLAYER_RADIUS) {
+        let sprite = &mut sprites[i];
+        let Some(item) = &sprite.item else { continue };
+        if sprite.collected {
+            continue;
+        }
+
+        let reach = sprite.radius + PLAYER_RADIUS;
+        let dx = sprite.pos.x - player.pos.x;
+        let dy = sprite.pos.y - player.pos.y;
+
+        if dx * dx + dy * dy <= reach * reach {
+            inventory.add(item);
+            // Desaparece cuando termina su clip `pickup`
+            sprite.collected = true;
+            sprite.animator.play("pickup");
+            collected.push(sprite.archetype);
+        }
+    }
+
+    collected
+}
@@ -0,0 +1,537 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::fs;
+use std::path::Path;
+
+use crate::archetypes::Archetypes;
+use crate::decals::{Decal, Face, Placement};
+use crate::doors::{Door, KeyColor};
+use crate::framebuffer::BlendMode;
+use crate::maze::{Maze, load_maze};
+use crate::objectives::{Objective, ObjectiveKind, Tally};
+use crate::pathfinding::{Cell, shortest_path};
+use crate::player::{Player, MOVE_SPEED};
+use crate::records::maze_hash;
+use crate::sprites::Sprite;
+
+// Las coordenadas de los niveles (valores por defecto y `.meta`) suponen celdas de este tamaño;
+// con otro `block_size` se escalan al cargar
+pub const LEVEL_BLOCK_SIZE: f32 = 80.0;
+
+// Margen sobre el recorrido ideal para giros y correcciones
+const PAR_SLACK: f32 = 1.25;
+
+// Lo que hace tocar una criatura
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContactDamage {
+    PerSecond(f32), // vida que quita por segundo de contacto
+    Kill,           // muerte instantánea
+}
+
+impl ContactDamage {
+    // Vida que queda tras `delta_time` segundos de contacto
+    pub fn apply(self, health: f32, delta_time: f32) -> f32 {
+        match self {
+            ContactDamage::PerSecond(damage) => health - damage * delta_time,
+            ContactDamage::Kill => 0.0,
+        }
+    }
+}
+
+// Estado inicial de un nivel, usado para arrancar y para reiniciar
+pub struct Level {
+    pub path: String,
+    pub maze: Maze,
+    pub player_start: Vector2,
+    pub player_angle: f32,
+    pub sprite_spawns: Vec<Sprite>, // tal como aparecen al iniciar; se clonan al reiniciar
+    pub activation_min_x: f32,
+    pub activation_min_y: f32,
+    pub goal_center: Vector2, // cerca del sprite prize
+    pub goal_radius: f32,
+    pub fog_enabled: bool,
+    pub contact_damage: ContactDamage,
+    pub doors: Vec<Door>,
+    pub objectives: Vec<Objective>,
+    pub decals: Vec<Decal>,
+    pub runes_total: u32, // sprites cuyo tipo cuenta como runa
+}
+
+impl Level {
+    // Carga el laberinto y, si existe, su archivo de metadatos (mismo nombre con extensión .meta)
+    // Los nombres de las líneas `sprite` se buscan en `archetypes`
+    pub fn load(path: &str, archetypes: &Archetypes, block_size: usize) -> Result<Self, String> {
+        if !Path::new(path).is_file() {
+            return Err(format!("no existe el nivel {}", path));
+        }
+
+        let maze = load_maze(path);
+        let doors = maze
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter().enumerate().filter_map(move |(x, &c)| {
+                    KeyColor::from_glyph(c).map(|color| Door { cell: (x, y), color, open: false })
+                })
+            })
+            .collect();
+
+        let mut level = Level {
+            path: path.to_string(),
+            maze,
+            player_start: Vector2::new(150.0, 150.0),
+            player_angle: PI / 3.0,
+            sprite_spawns: [("creature", 500.0, 100.0), ("prize", 850.0, 875.0)]
+                .into_iter()
+                .filter_map(|(name, x, y)| archetypes.find(name).map(|id| Sprite::new(x, y, id, archetypes.get(id))))
+                .collect(),
+            activation_min_x: 327.0,
+            activation_min_y: 160.0,
+            goal_center: Vector2::new(850.0, 875.0),
+            goal_radius: 140.0,
+            fog_enabled: true,
+            contact_damage: ContactDamage::PerSecond(60.0),
+            doors,
+            objectives: vec![Objective { kind: ObjectiveKind::ReachExit, required: true }],
+            decals: Vec::new(),
+            runes_total: 0,
+        };
+
+        let meta_path = Path::new(path).with_extension("meta");
+        if meta_path.is_file() {
+            level.load_meta(&meta_path.to_string_lossy(), archetypes)?;
+        }
+        level.scale(block_size as f32 / LEVEL_BLOCK_SIZE);
+        level.runes_total = level.count_runes(archetypes);
+
+        Ok(level)
+    }
+
+    // Sprites cuyo tipo cuenta como runa
+    fn count_runes(&self, archetypes: &Archetypes) -> u32 {
+        self.sprite_spawns
+            .iter()
+            .filter(|s| archetypes.get(s.archetype).roles.counts_for == Some(Tally::Runes))
+            .count() as u32
+    }
+
+    // Lleva las posiciones del mundo al tamaño de celda real (las de celda, como las puertas, no cambian)
+    fn scale(&mut self, factor: f32) {
+        if factor == 1.0 {
+            return;
+        }
+        self.player_start *= factor;
+        self.goal_center *= factor;
+        self.goal_radius *= factor;
+        self.activation_min_x *= factor;
+        self.activation_min_y *= factor;
+        for sprite in &mut self.sprite_spawns {
+            sprite.pos *= factor;
+            if let Some(brain) = &mut sprite.brain {
+                brain.home *= factor;
+                for waypoint in &mut brain.waypoints {
+                    *waypoint *= factor;
+                }
+            }
+        }
+        for decal in &mut self.decals {
+            if let Placement::Free { a, b } = &mut decal.placement {
+                *a *= factor;
+                *b *= factor;
+            }
+        }
+    }
+
+    // Formato: una entrada por línea, `clave valores...`, con comentarios `#`.
+    // Si hay líneas `sprite` u `objective`, reemplazan a los sprites u objetivos por defecto.
+    fn load_meta(&mut self, path: &str, archetypes: &Archetypes) -> Result<(), String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut sprites = Vec::new();
+        let mut objectives = Vec::new();
+        let mut runes_line = None; // línea del objetivo `runes`, si lo hay
+        let mut last_is_decal = false; // a qué se aplica una línea `blend`
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut parts = line.split_whitespace();
+            let Some(key) = parts.next() else { continue };
+            let values: Vec<&str> = parts.collect();
+
+            let err = |msg: &str| format!("{}:{}: {}", path, i + 1, msg);
+            let num = |idx: usize| -> Result<f32, String> {
+                values
+                    .get(idx)
+                    .and_then(|v| v.parse::<f32>().ok())
+                    .ok_or_else(|| err(&format!("valor numérico inválido en '{}'", key)))
+            };
+
+            let is_decal = key == "decal";
+            match key {
+                "spawn" => {
+                    self.player_start = Vector2::new(num(0)?, num(1)?);
+                    self.player_angle = num(2)?.to_radians();
+                }
+                "goal" => {
+                    self.goal_center = Vector2::new(num(0)?, num(1)?);
+                    self.goal_radius = num(2)?;
+                }
+                "zone" => {
+                    self.activation_min_x = num(0)?;
+                    self.activation_min_y = num(1)?;
+                }
+                "fog" => {
+                    self.fog_enabled = match values.first() {
+                        Some(&"on") => true,
+                        Some(&"off") => false,
+                        _ => return Err(err("fog: se esperaba on u off")),
+                    }
+                }
+                "contact_damage" => {
+                    // `kill` termina la partida al primer contacto
+                    self.contact_damage = match values.first() {
+                        Some(&"kill") => ContactDamage::Kill,
+                        _ => {
+                            let damage = num(0)?;
+                            if !damage.is_finite() || damage < 0.0 {
+                                return Err(err("contact_damage: se esperaba un número no negativo o kill"));
+                            }
+                            ContactDamage::PerSecond(damage)
+                        }
+                    }
+                }
+                "sprite" => {
+                    let name = values.first().ok_or_else(|| err("sprite: falta el tipo"))?;
+                    let id = archetypes
+                        .find(name)
+                        .ok_or_else(|| err(&format!("tipo de sprite desconocido '{}'", name)))?;
+                    let mut spawn = Sprite::new(num(1)?, num(2)?, id, archetypes.get(id));
+                    // Opcionales: alto y altura sobre el piso
+                    if values.len() > 3 {
+                        spawn.size = num(3)?;
+                    }
+                    if values.len() > 4 {
+                        spawn.z_offset = num(4)?;
+                    }
+                    sprites.push(spawn);
+                }
+                "patrol" => {
+                    // Puntos x y de patrulla para el sprite de la línea anterior
+                    let spawn = sprites.last_mut().ok_or_else(|| err("patrol: debe ir después de una línea sprite"))?;
+                    let brain = spawn.brain.as_mut().ok_or_else(|| err("patrol: el sprite anterior no es una criatura"))?;
+                    if values.is_empty() || !values.len().is_multiple_of(2) {
+                        return Err(err("patrol: se esperaban pares x y"));
+                    }
+                    for i in (0..values.len()).step_by(2) {
+                        brain.waypoints.push(Vector2::new(num(i)?, num(i + 1)?));
+                    }
+                }
+                "objective" => {
+                    let objective = Objective::parse(&values).map_err(|e| err(&e))?;
+                    if objective.kind == ObjectiveKind::FindAllRunes {
+                        runes_line = Some(i + 1);
+                    }
+                    objectives.push(objective);
+                }
+                "decal" => {
+                    // `decal wall celda_x celda_y n|s|e|w imagen [u0 u1 z0 z1]`
+                    // `decal free x0 y0 x1 y1 imagen [z0 z1]`
+                    let (placement, texture, extra) = match values.first() {
+                        Some(&"wall") => {
+                            let face = values
+                                .get(3)
+                                .and_then(|v| Face::parse(v))
+                                .ok_or_else(|| err("decal wall: se esperaba la cara n, s, e o w"))?;
+                            let (u0, u1) = if values.len() > 6 { (num(5)?, num(6)?) } else { (0.0, 1.0) };
+                            let cell = (num(1)? as usize, num(2)? as usize);
+                            (Placement::Wall { cell, face, u0, u1 }, values.get(4), 7)
+                        }
+                        Some(&"free") => {
+                            let a = Vector2::new(num(1)?, num(2)?);
+                            let b = Vector2::new(num(3)?, num(4)?);
+                            (Placement::Free { a, b }, values.get(5), 6)
+                        }
+                        _ => return Err(err("decal: se esperaba wall o free")),
+                    };
+                    let texture = texture.ok_or_else(|| err("decal: falta la imagen"))?;
+                    let mut decal = Decal::new(placement, texture);
+                    if values.len() > extra + 1 {
+                        decal.z0 = num(extra)?;
+                        decal.z1 = num(extra + 1)?;
+                    }
+                    self.decals.push(decal);
+                }
+                "blend" => {
+                    // Modo de mezcla y opacidad para el sprite o decal de la línea anterior
+                    let mode = values
+                        .first()
+                        .and_then(|v| BlendMode::parse(v))
+                        .ok_or_else(|| err("blend: se esperaba alpha, additive o multiply"))?;
+                    let opacity = if values.len() > 1 { num(1)?.clamp(0.0, 1.0) } else { 1.0 };
+
+                    if last_is_decal {
+                        let decal = self.decals.last_mut().ok_or_else(|| err("blend: falta el decal"))?;
+                        decal.blend = mode;
+                        decal.opacity = opacity;
+                    } else {
+                        let spawn = sprites.last_mut().ok_or_else(|| err("blend: debe ir después de una línea sprite o decal"))?;
+                        spawn.blend = mode;
+                        spawn.opacity = opacity;
+                    }
+                }
+                _ => return Err(err(&format!("clave desconocida '{}'", key))),
+            }
+
+            if key == "sprite" || is_decal {
+                last_is_decal = is_decal;
+            }
+        }
+
+        if !sprites.is_empty() {
+            self.sprite_spawns = sprites;
+        }
+        // Sin runas en el nivel el objetivo estaría cumplido desde el primer frame
+        if let Some(line) = runes_line
+            && self.count_runes(archetypes) == 0
+        {
+            return Err(format!("{}:{}: objective runes: el nivel no tiene runas", path, line));
+        }
+        if !objectives.is_empty() {
+            if !objectives.iter().any(|o| o.required) {
+                return Err(format!("{}: hace falta al menos un objetivo obligatorio", path));
+            }
+            self.objectives = objectives;
+        }
+        Ok(())
+    }
+
+    pub fn spawn_player(&self, fov: f32) -> Player {
+        Player { pos: self.player_start, a: self.player_angle, fov }
+    }
+
+    // Tiempo par: camino más corto (BFS) entre el spawn y la meta recorrido a velocidad máxima
+    pub fn par_time(&self, block_size: usize, fps: f32) -> Option<f32> {
+        let to_cell = |p: Vector2| ((p.x / block_size as f32) as usize, (p.y / block_size as f32) as usize);
+        // Las puertas cuentan como abiertas: el par no incluye el desvío por las llaves
+        let mut maze = self.maze.clone();
+        for door in &self.doors {
+            maze[door.cell.1][door.cell.0] = ' ';
+        }
+        let path = shortest_path(&maze, to_cell(self.player_start), to_cell(self.goal_center))?;
+
+        let distance = ((path.len() - 1) as f32 * block_size as f32 - self.goal_radius).max(0.0);
+        Some(distance / (MOVE_SPEED * fps) * PAR_SLACK)
+    }
+
+    // Identifica el nivel en los récords; se calcula con las puertas cerradas para que no cambie al abrirlas
+    pub fn hash(&self) -> u64 {
+        let mut maze = self.maze.clone();
+        for door in &self.doors {
+            maze[door.cell.1][door.cell.0] = door.color.glyph();
+        }
+        maze_hash(&maze)
+    }
+
+    // Color de la puerta cerrada en `cell`, si la hay
+    pub fn door_color(&self, cell: Cell) -> Option<KeyColor> {
+        self.doors.iter().find(|d| d.cell == cell && !d.open).map(|d| d.color)
+    }
+
+    // Abre la puerta y las celdas vecinas del mismo color (una puerta puede ocupar varias celdas)
+    pub fn open_door(&mut self, cell: Cell) {
+        let Some(color) = self.door_color(cell) else { return };
+        let mut pending = vec![cell];
+
+        while let Some((x, y)) = pending.pop() {
+            let Some(door) = self.doors.iter_mut().find(|d| d.cell == (x, y) && !d.open && d.color == color) else {
+                continue;
+            };
+            door.open = true;
+            self.maze[y][x] = ' ';
+
+            pending.extend([(x + 1, y), (x, y + 1)]);
+            if x > 0 {
+                pending.push((x - 1, y));
+            }
+            if y > 0 {
+                pending.push((x, y - 1));
+            }
+        }
+    }
+
+    // Vuelve a cerrar todas las puertas (al reiniciar o cargar una partida)
+    pub fn close_doors(&mut self) {
+        for door in &mut self.doors {
+            door.open = false;
+            self.maze[door.cell.1][door.cell.0] = door.color.glyph();
+        }
+    }
+
+    pub fn opened_doors(&self) -> Vec<Cell> {
+        self.doors.iter().filter(|d| d.open).map(|d| d.cell).collect()
+    }
+
+    pub fn spawn_sprites(&self) -> Vec<Sprite> {
+        self.sprite_spawns.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{TempFile, SMALL_MAZE};
+
+    fn load(name: &str, meta: &str, block_size: usize) -> Result<Level, String> {
+        let _meta = TempFile::new(&format!("{}.meta", name), meta);
+        let file = TempFile::new(&format!("{}.txt", name), SMALL_MAZE);
+        Level::load(file.path(), &Archetypes::builtin(), block_size)
+    }
+
+    const META: &str = "\
+spawn 120 100 90
+goal 400 200 60
+zone 300 80
+fog off
+contact_damage kill
+sprite creature 200 100
+patrol 200 100 400 100
+sprite rune 100 200 20 35
+blend additive 0.5
+sprite rune 400 240
+decal free 0 0 80 0 assets/hiedra.png 10 60
+blend multiply
+objective runes
+objective gems 2 optional
+";
+
+    #[test]
+    fn world_coordinates_scale_with_block_size() {
+        let level = load("level_scaled", META, 40).unwrap();
+        assert_eq!(level.player_start, Vector2::new(60.0, 50.0));
+        assert_eq!((level.goal_center, level.goal_radius), (Vector2::new(200.0, 100.0), 30.0));
+        assert_eq!((level.activation_min_x, level.activation_min_y), (150.0, 40.0));
+        let brain = level.sprite_spawns[0].brain.as_ref().unwrap();
+        assert_eq!(level.sprite_spawns[0].pos, Vector2::new(100.0, 50.0));
+        assert_eq!(brain.waypoints[1], Vector2::new(200.0, 50.0));
+        match level.decals[0].placement {
+            Placement::Free { b, .. } => assert_eq!(b, Vector2::new(40.0, 0.0)),
+            _ => panic!("se esperaba un decal libre"),
+        }
+        // Las alturas no dependen del tamaño de celda
+        assert_eq!(level.sprite_spawns[1].size, 20.0);
+    }
+
+    #[test]
+    fn loads_the_meta_file() {
+        let level = load("level_meta", META, 80).unwrap();
+        assert_eq!(level.player_start, Vector2::new(120.0, 100.0));
+        assert!((level.player_angle - PI / 2.0).abs() < 1e-5);
+        assert_eq!((level.goal_center, level.goal_radius), (Vector2::new(400.0, 200.0), 60.0));
+        assert!(!level.fog_enabled);
+        assert_eq!(level.contact_damage, ContactDamage::Kill);
+        assert_eq!(level.sprite_spawns.len(), 3);
+        assert_eq!(level.sprite_spawns[0].brain.as_ref().unwrap().waypoints.len(), 2);
+        let rune = &level.sprite_spawns[1];
+        assert_eq!((rune.size, rune.z_offset, rune.blend, rune.opacity), (20.0, 35.0, BlendMode::Additive, 0.5));
+        assert_eq!(level.runes_total, 2);
+        assert_eq!(level.decals.len(), 1);
+        assert_eq!((level.decals[0].z0, level.decals[0].blend), (10.0, BlendMode::Multiply));
+        assert_eq!(level.objectives.len(), 2);
+        assert_eq!(level.doors.len(), 1);
+    }
+
+    #[test]
+    fn defaults_without_meta() {
+        let file = TempFile::new("level_plain.txt", SMALL_MAZE);
+        let level = Level::load(file.path(), &Archetypes::builtin(), 80).unwrap();
+        assert_eq!(level.contact_damage, ContactDamage::PerSecond(60.0));
+        assert_eq!(level.objectives.len(), 1);
+        assert_eq!(level.runes_total, 0);
+        assert!(Level::load("no-existe.txt", &Archetypes::builtin(), 80).is_err());
+    }
+
+    // Cada caso tiene que fallar en su última línea con un mensaje que contenga el texto esperado
+    fn assert_meta_errors(prefix: &str, cases: &[(&str, &str)]) {
+        for (i, (meta, expected)) in cases.iter().enumerate() {
+            let err = load(&format!("{}_{}", prefix, i), meta, 80).err().unwrap();
+            let line = meta.lines().count();
+            assert!(err.contains(&format!(".meta:{}:", line)) && err.contains(expected), "{}: {}", meta, err);
+        }
+    }
+
+    #[test]
+    fn meta_errors_name_file_and_line() {
+        assert_meta_errors(
+            "level_bad",
+            &[
+                ("spawn 1 2", "valor numérico"),
+                ("fog maybe", "fog"),
+                ("sprite dragon 1 2", "desconocido 'dragon'"),
+                ("sprite", "falta el tipo"),
+                ("teleport 1 2", "clave desconocida"),
+            ],
+        );
+    }
+
+    #[test]
+    fn patrol_errors() {
+        assert_meta_errors(
+            "level_patrol",
+            &[
+                ("patrol 1 2", "después de una línea sprite"),
+                ("sprite rune 1 2\npatrol 1 2", "no es una criatura"),
+                ("sprite creature 1 2\npatrol 1 2 3", "pares x y"),
+            ],
+        );
+    }
+
+    #[test]
+    fn contact_damage_values() {
+        let damage = |value: &str| load("level_damage", &format!("contact_damage {}", value), 80).map(|l| l.contact_damage);
+        assert_eq!(damage("25"), Ok(ContactDamage::PerSecond(25.0)));
+        assert_eq!(damage("0"), Ok(ContactDamage::PerSecond(0.0)));
+        assert!(damage("-5").is_err());
+        assert!(damage("inf").is_err());
+        assert!(damage("NaN").is_err());
+        // `kill` mata aunque el frame no haya durado nada
+        assert_eq!(ContactDamage::Kill.apply(100.0, 0.0), 0.0);
+        assert_eq!(ContactDamage::PerSecond(10.0).apply(100.0, 0.5), 95.0);
+    }
+
+    #[test]
+    fn objective_errors() {
+        assert_meta_errors(
+            "level_objective",
+            &[
+                ("objective treasure", "objetivo desconocido"),
+                ("objective gems -1", "entero positivo"),
+                ("objective gems 0", "entero positivo"),
+                ("objective gems 0.5", "entero positivo"),
+                ("objective survive 0", "número positivo"),
+                ("objective runes", "no tiene runas"),
+                ("sprite gem 1 2\nobjective runes", "no tiene runas"),
+            ],
+        );
+        let err = load("level_optional", "objective exit optional", 80).err().unwrap();
+        assert!(err.contains("al menos un objetivo obligatorio"), "{}", err);
+    }
+
+    #[test]
+    fn blend_errors() {
+        assert_meta_errors(
+            "level_blend",
+            &[("blend alpha", "después de una línea sprite o decal"), ("sprite rune 1 2\nblend glow", "blend")],
+        );
+    }
+
+    #[test]
+    fn decal_errors() {
+        assert_meta_errors(
+            "level_decal",
+            &[
+                ("decal roof 1 1 n a.png", "wall o free"),
+                ("decal wall 1 1 up a.png", "la cara"),
+                ("decal free 0 0 1 1", "falta la imagen"),
+            ],
+        );
+    }
+}
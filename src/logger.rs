@@ -0,0 +1,89 @@
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub fn parse(value: &str) -> Option<LogLevel> {
+        match value.to_lowercase().as_str() {
+            "error" => Some(LogLevel::Error),
+            "warn" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+        }
+    }
+}
+
+struct Logger {
+    level: LogLevel,
+    file: Option<File>,
+}
+
+static LOGGER: Mutex<Logger> = Mutex::new(Logger { level: LogLevel::Info, file: None });
+static START: OnceLock<Instant> = OnceLock::new();
+
+// Sin archivo, los mensajes van a stderr
+pub fn init(level: LogLevel, path: Option<&str>) -> Result<(), String> {
+    let file = match path {
+        Some(path) => Some(File::create(path).map_err(|e| format!("no se pudo crear el log {}: {}", path, e))?),
+        None => None,
+    };
+
+    START.get_or_init(Instant::now);
+    let mut logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    logger.level = level;
+    logger.file = file;
+    Ok(())
+}
+
+pub fn log(level: LogLevel, args: fmt::Arguments) {
+    let mut logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    if level > logger.level {
+        return;
+    }
+
+    let elapsed = START.get_or_init(Instant::now).elapsed().as_secs_f32();
+    let line = format!("[{:9.3}] {:5} {}\n", elapsed, level.label(), args);
+
+    match &mut logger.file {
+        Some(file) => {
+            let _ = file.write_all(line.as_bytes());
+        }
+        None => eprint!("{}", line),
+    }
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => { $crate::logger::log($crate::logger::LogLevel::Error, format_args!($($arg)*)) };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => { $crate::logger::log($crate::logger::LogLevel::Warn, format_args!($($arg)*)) };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => { $crate::logger::log($crate::logger::LogLevel::Info, format_args!($($arg)*)) };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => { $crate::logger::log($crate::logger::LogLevel::Debug, format_args!($($arg)*)) };
+}
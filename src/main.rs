@@ -1,6 +1,8 @@
 #![allow(unused_imports)]
 #![allow(dead_code)]
 
+#[macro_use]
+mod logger;
 mod framebuffer;
 mod maze;
 mod player;
@@ -8,6 +10,27 @@ mod caster;
 mod textures;
 mod sprites;
 mod audio;
+mod level;
+mod pathfinding;
+mod records;
+mod save;
+mod config;
+mod debug;
+mod minimap;
+mod fog;
+mod rng;
+mod ai;
+mod inventory;
+mod doors;
+mod objectives;
+mod animation;
+mod decals;
+mod spatial;
+mod particles;
+mod archetypes;
+mod assets;
+#[cfg(test)]
+mod testutil;
 
 use raylib::prelude::*;
 use raylib::text::measure_text; 
@@ -23,9 +46,25 @@ use textures::TextureManager;
 use sprites::Sprite;
 use raylib::ffi::TraceLogLevel;
 use audio::AudioSystem;
-use crate::sprites::SpriteType;
+use archetypes::Archetypes;
+use assets::{AssetReport, Manifest, MANIFEST_FILE};
+use level::{ContactDamage, Level};
+use records::{Records, RECORDS_FILE, maze_hash, format_time};
+use save::{SaveData, SAVE_FILE, QUICKSAVE_FILE};
+use config::{Config, MAX_MINIMAP_RAYS, USAGE};
+use debug::{DebugOverlay, Stage};
+use minimap::{MapOverlay, MapScene, Minimap};
+use fog::FogOfWar;
+use rng::Rng;
+use spatial::SpatialIndex;
+use particles::{EmitterConfig, ParticleSystem, VisibleParticle};
+use inventory::Inventory;
+use doors::KeyColor;
+use objectives::{Objective, ObjectiveContext, Progress, Tally};
 
 const TRANSPARENT_COLOR: Color = Color::new(0, 0, 0, 0);
+const WALL_HEIGHT: f32 = 100.0; // unidades del mundo
+const NEAR_PLANE: f32 = 1.0;
 
 fn draw_sprite(
     framebuffer: &mut Framebuffer,
@@ -37,144 +76,89 @@ fn draw_sprite(
         return;
     }
 
-    // Calcular ángulo del sprite relativo al jugador
-    let sprite_a = (sprite.pos.y - player.pos.y).atan2(sprite.pos.x - player.pos.x);
-    let mut angle_diff = sprite_a - player.a;
-    
-    // Normalizar el ángulo
-    while angle_diff > PI {
-        angle_diff -= 2.0 * PI;
-    }
-    while angle_diff < -PI {
-        angle_diff += 2.0 * PI;
-    }
+    // Posición en el espacio de la cámara: `depth` hacia adelante (como la distancia corregida
+    // de las paredes) y `side` hacia la derecha
+    let dx = sprite.pos.x - player.pos.x;
+    let dy = sprite.pos.y - player.pos.y;
+    let (sin_a, cos_a) = player.a.sin_cos();
+    let depth = dx * cos_a + dy * sin_a;
+    let side = -dx * sin_a + dy * cos_a;
 
-    // Verificar si el sprite está dentro del campo de visión
-    if angle_diff.abs() > player.fov / 2.0 {
+    // Detrás de la cámara o pegado a ella
+    if depth < NEAR_PLANE {
         return;
     }
 
-    let sprite_d = (player.pos.x - sprite.pos.x).hypot(player.pos.y - sprite.pos.y);
+    // Hoja del tipo de sprite; si no se pudo cargar, ya se avisó al iniciar
+    let Some(sheet) = texture_manager.get_sprite_sheet(sprite.archetype) else {
+        return;
+    };
 
-    // Distancia perpendicular (para z-buffer)
-    let sprite_perp = (sprite_d * angle_diff.cos().abs()).max(0.0001);
+    // Frame actual; la vista depende del ángulo entre la criatura y el jugador
+    let direction = sprite.view_direction(player.pos, sheet.directions);
+    let frame = sheet.frame(sprite.current_frame, direction);
+    let rect = frame.rect;
+    if rect.width < 1.0 || rect.height < 1.0 || sheet.reference_height < 1.0 {
+        return;
+    }
 
-    let screen_height = framebuffer.height as f32;
     let screen_width = framebuffer.width as f32;
+    let screen_height = framebuffer.height as f32;
+    let hh = screen_height / 2.0;
+
+    // Misma proyección que las paredes: columna según el ángulo del rayo y `hh / depth` píxeles por
+    // unidad del mundo en vertical; el ojo está a media altura del muro. En horizontal, un ángulo de
+    // `fov` ocupa todo el ancho: `screen_width / (fov * depth)` píxeles por unidad.
+    // `size` es el alto de un frame de referencia, así los frames más chicos o más grandes conservan la escala.
+    let scale = hh / depth;
+    let scale_x = screen_width / (player.fov * depth);
+    let screen_x = (side.atan2(depth) / player.fov + 0.5) * screen_width;
+    // Píxeles de pantalla por píxel de la hoja
+    let texel_x = sprite.size * scale_x / sheet.reference_height;
+    let texel_y = sprite.size * scale / sheet.reference_height;
+    let width = rect.width * texel_x;
+    let height = rect.height * texel_y;
+
+    // El pivote del frame se apoya en la posición del sprite, a `z_offset` sobre el piso
+    let base_y = hh - (sprite.z_offset - WALL_HEIGHT / 2.0) * scale;
+    let left = screen_x - frame.pivot.x * texel_x;
+    let top = base_y - frame.pivot.y * texel_y;
+
+    let start_x = left.max(0.0) as i32;
+    let end_x = (left + width).min(screen_width) as i32;
+    let start_y = top.max(0.0) as i32;
+    let end_y = (top + height).min(screen_height) as i32;
+    if start_x >= end_x || start_y >= end_y {
+        return;
+    }
 
-    if sprite_perp < 30.0 || sprite_perp > 600.0 { return; }
-
-    // Calcular tamaño del sprite en pantalla
-    let sprite_size = (screen_height / sprite_d) * 70.0;
-    
-    // Calcular posición X en pantalla
-    let screen_x = ((angle_diff / player.fov) + 0.5) * screen_width;
-
-    // Calcular bounds del sprite
-    let start_x = (screen_x - sprite_size / 2.0).max(0.0) as i32;
-    let end_x = (screen_x + sprite_size / 2.0).min(screen_width) as i32;
-    let start_y = (screen_height / 2.0 - sprite_size / 2.0).max(0.0) as i32;
-    let end_y = (screen_height / 2.0 + sprite_size / 2.0).min(screen_height) as i32;
-
-    // Obtener información de la animación usando SpriteType
-    let (_, image, total_frames) = match texture_manager.animations.get(&sprite.sprite_type) {
-        Some(data) => data,
-        None => {
-            eprintln!("Missing animation for sprite type: {:?}", sprite.sprite_type);
-            return;
-        }
-    };
-
-    let frame_width = image.width as u32 / *total_frames as u32;
-    let frame_height = image.height as u32;
-
-    // Obtener coordenadas del frame actual
-    let frame_x = sprite.current_frame as u32 * frame_width;
-    let frame_y = 0;
+    let (frame_x, frame_y) = (rect.x as u32, rect.y as u32);
+    let (frame_width, frame_height) = (rect.width as u32, rect.height as u32);
 
     // Dibujar el sprite
     for x in start_x..end_x {
-        let xi = x.clamp(0, framebuffer.width as i32 - 1) as usize;
-
         // Si el sprite en esta columna está detrás del muro, NO pintes
-        if sprite_perp >= framebuffer.depth_buffer[xi] {
+        if !framebuffer.column_visible(x as u32, depth) {
             continue;
         }
 
-        for y in start_y..end_y {
-            // Mapear coordenadas de pantalla a coordenadas de textura
-            let tex_x = ((x - start_x) as f32 / (end_x - start_x) as f32) * frame_width as f32;
-            let tex_y = ((y - start_y) as f32 / (end_y - start_y) as f32) * frame_height as f32;
-            
-            let final_tx = (frame_x as f32 + tex_x) as u32;
-            let final_ty = (frame_y as f32 + tex_y) as u32;
-
-            let color = texture_manager.get_sprite_pixel_color(&sprite.sprite_type, final_tx, final_ty);
-            
-            // Solo dibujar píxeles no transparentes
-            if color.a > 0 && color != TRANSPARENT_COLOR {
-                framebuffer.set_pixel_fast(x as u32, y as u32, color);
-            }
-        }
-    }
-}
-
-fn draw_cell(
-    framebuffer: &mut Framebuffer,
-    xo: usize,
-    yo: usize,
-    block_size: usize,
-    cell: char,
-) {
-    if cell == ' ' {
-        return;
-    }
+        // Las coordenadas de textura salen del rectángulo sin recortar, así no se deforma en los bordes
+        let tex_x = ((x as f32 - left) / texel_x) as u32;
 
-    framebuffer.set_current_color(Color::new(48, 35, 61, 255));
+        for y in start_y..end_y {
+            let tex_y = ((y as f32 - top) / texel_y) as u32;
 
-    for x in xo..xo + block_size {
-        for y in yo..yo + block_size {
-            framebuffer.set_pixel(x as u32, y as u32);
-        }
-    }
-}
+            let final_tx = frame_x + tex_x.min(frame_width - 1);
+            let final_ty = frame_y + tex_y.min(frame_height - 1);
 
-pub fn render_maze(
-    framebuffer: &mut Framebuffer,
-    maze: &Maze,
-    block_size: usize,
-    player: &Player,
-) {
-    for (row_index, row) in maze.iter().enumerate() {
-        for (col_index, &cell) in row.iter().enumerate() {
-            let xo = col_index * block_size;
-            let yo = row_index * block_size;
+            let color = texture_manager.get_sprite_pixel_color(sprite.archetype, final_tx, final_ty);
             
-            draw_cell(framebuffer, xo, yo, block_size, cell);
-        }
-    }
-
-    //draw player
-    framebuffer.set_current_color(Color::WHITE);
-    let px = (player.pos.x / block_size as f32 * block_size as f32) as u32;
-    let py = (player.pos.y / block_size as f32 * block_size as f32) as u32;
-    framebuffer.set_pixel(px, py);
-
-    for x in px.saturating_sub(1)..=px.saturating_add(1) {
-        for y in py.saturating_sub(1)..=py.saturating_add(1) {
-            if x < framebuffer.width && y < framebuffer.height {
-                framebuffer.set_pixel(x, y);
+            // Solo dibujar píxeles no transparentes; con profundidad por píxel también se ocultan entre sprites
+            if color.a > 0 && color != TRANSPARENT_COLOR {
+                framebuffer.blend_pixel_depth(x as u32, y as u32, color, sprite.blend, sprite.opacity, depth);
             }
         }
     }
-
-    // draw what the player sees
-    let num_rays = 3;
-    for i in 0..num_rays {
-        let current_ray = i as f32 / num_rays as f32; // current ray divided by total rays
-        let a = (player.a - (player.fov / 2.0)) + (player.fov * current_ray);
-        cast_ray(framebuffer, &maze, &player, block_size, a, true);
-    }
 }
 
 pub fn render_3D(
@@ -183,7 +167,7 @@ pub fn render_3D(
     block_size: usize,
     player: &Player,
     texture_cache: &TextureManager,
-    game_state: &GameState
+    game_state: &mut GameState
 ){
     let num_rays = framebuffer.width;
     let hh = framebuffer.height as f32 /2.0;
@@ -191,11 +175,13 @@ pub fn render_3D(
     let fov_half = player.fov / 2.0;
     let fov_step = player.fov / num_rays as f32;
 
+    game_state.fog.begin_frame();
+
     for i in 0..num_rays{
         let a = player.a - fov_half + (i as f32 * fov_step);
         let angle_diff = a - player.a;
 
-        let intersect = cast_ray(framebuffer, maze, player, block_size, a, false);
+        let intersect = cast_ray(framebuffer, maze, player, block_size, a, None, Some(&mut game_state.fog));
         let d = intersect.distance;
         let c = intersect.impact;
         
@@ -205,7 +191,7 @@ pub fn render_3D(
 
         framebuffer.depth_buffer[i as usize] = correct_distance.max(0.0001);
         
-        let stake_height = (hh / correct_distance) * 100.0;
+        let stake_height = (hh / correct_distance) * WALL_HEIGHT;
         let half_stake_height = stake_height / 2.0;
         let stake_top = (hh - half_stake_height).max(0.0) as u32;
         let stake_bottom = (hh + half_stake_height).min(framebuffer.height as f32) as u32;
@@ -227,44 +213,46 @@ pub fn render_3D(
                 if idx < framebuffer.color_buffer.len() {
                     framebuffer.color_buffer[idx] = color;
                 }
+                framebuffer.write_depth(i, y, correct_distance);
                 
                 ty += ty_step;
             }
         }
     }
+}
 
-    if game_state.flashlight_active {
-
-        let center_x = framebuffer.width as f32 / 2.0;
-        let center_y = framebuffer.height as f32/ 2.0 ;
-        let radius = framebuffer.height as f32 * 0.2;
-        let aspect_ratio = framebuffer.width as f32 / framebuffer.height as f32;
-
-        for y in 0..framebuffer.height {
-            for x in 0..framebuffer.width {
-                // Coordenadas normalizadas (-1 a 1) considerando relación de aspecto
-                let nx = (x as f32 - center_x) / (radius * aspect_ratio);
-                let ny = (y as f32 - center_y) / radius;
-
-                // Distancia al centro (corregida por relación de aspecto)
-                let dist_squared = nx * nx + ny * ny;
-
-                if dist_squared > 1.0 { // Fuera del círculo
-                    let idx = (y * framebuffer.width + x) as usize;
-                    framebuffer.color_buffer[idx] = Color::BLACK;
-                }
-                // Opcional: Suavizado de bordes
-                else if dist_squared > 0.7 {
-                    let fade = 1.0 - ((dist_squared - 0.7) / 0.3).min(1.0);
-                    let idx = (y * framebuffer.width + x) as usize;
-                    let color = framebuffer.color_buffer[idx];
-                    framebuffer.color_buffer[idx] = Color::new(
-                        (color.r as f32 * fade) as u8,
-                        (color.g as f32 * fade) as u8,
-                        (color.b as f32 * fade) as u8,
-                        255
-                    );
-                }
+// Linterna de la zona especial: oscurece todo lo que queda fuera del círculo central.
+// Va después de decals, sprites y partículas para taparlos también
+fn render_flashlight(framebuffer: &mut Framebuffer) {
+    let center_x = framebuffer.width as f32 / 2.0;
+    let center_y = framebuffer.height as f32/ 2.0 ;
+    let radius = framebuffer.height as f32 * 0.2;
+    let aspect_ratio = framebuffer.width as f32 / framebuffer.height as f32;
+
+    for y in 0..framebuffer.height {
+        for x in 0..framebuffer.width {
+            // Coordenadas normalizadas (-1 a 1) considerando relación de aspecto
+            let nx = (x as f32 - center_x) / (radius * aspect_ratio);
+            let ny = (y as f32 - center_y) / radius;
+
+            // Distancia al centro (corregida por relación de aspecto)
+            let dist_squared = nx * nx + ny * ny;
+
+            if dist_squared > 1.0 { // Fuera del círculo
+                let idx = (y * framebuffer.width + x) as usize;
+                framebuffer.color_buffer[idx] = Color::BLACK;
+            }
+            // Opcional: Suavizado de bordes
+            else if dist_squared > 0.7 {
+                let fade = 1.0 - ((dist_squared - 0.7) / 0.3).min(1.0);
+                let idx = (y * framebuffer.width + x) as usize;
+                let color = framebuffer.color_buffer[idx];
+                framebuffer.color_buffer[idx] = Color::new(
+                    (color.r as f32 * fade) as u8,
+                    (color.g as f32 * fade) as u8,
+                    (color.b as f32 * fade) as u8,
+                    255
+                );
             }
         }
     }
@@ -272,30 +260,61 @@ pub fn render_3D(
 
 
 
+// Lo que se dibuja ordenado por profundidad después de las paredes
+enum Billboard<'a> {
+    Sprite(&'a Sprite),
+    Particle(VisibleParticle),
+}
+
 fn render_sprites(
     framebuffer: &mut Framebuffer,
     player: &Player,
     sprites: &[Sprite],
+    particles: &ParticleSystem,
+    spatial: &SpatialIndex,
+    fog: &FogOfWar,
     texture_cache: &TextureManager,
 ) {
-    let mut ordered: Vec<&Sprite> = sprites.iter().collect();
-    ordered.sort_by(|a, b| {
-        let da = (player.pos.x - a.pos.x).hypot(player.pos.y - a.pos.y);
-        let db = (player.pos.x - b.pos.x).hypot(player.pos.y - b.pos.y);
-        db.partial_cmp(&da).unwrap_or(std::cmp::Ordering::Equal)
-    });
-
-    for sprite in ordered {
-        draw_sprite(framebuffer, player, sprite, texture_cache);
+    // Sólo los sprites y partículas en celdas que los rayos tocaron este frame (o junto a ellas)
+    let (sin_a, cos_a) = player.a.sin_cos();
+    let mut ordered: Vec<(f32, Billboard)> = spatial
+        .query_visible(|x, y| fog.is_visible(x, y))
+        .into_iter()
+        .map(|i| {
+            let s = &sprites[i];
+            ((s.pos.x - player.pos.x) * cos_a + (s.pos.y - player.pos.y) * sin_a, Billboard::Sprite(s))
+        })
+        .collect();
+    ordered.extend(
+        particles
+            .visible(player, spatial, |x, y| fog.is_visible(x, y))
+            .into_iter()
+            .map(|p| (p.depth, Billboard::Particle(p))),
+    );
+
+    // Del más lejano al más cercano, por profundidad en el espacio de la cámara; así, sin buffer por
+    // píxel, una partícula detrás de una criatura no se pinta encima
+    ordered.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    for (_, billboard) in ordered {
+        match billboard {
+            Billboard::Sprite(sprite) => draw_sprite(framebuffer, player, sprite, texture_cache),
+            Billboard::Particle(particle) => particles.draw(framebuffer, player, &particle, texture_cache),
+        }
     }
 }
 
 #[derive(PartialEq, Eq)]
 enum Mode {
     Playing,
+    Paused,
     Success,
+    GameOver,
 }
 
+const MAX_HEALTH: f32 = 100.0;
+const JUMPSCARE_DURATION: f32 = 1.2;
+
 pub struct GameState {
     pub flashlight_active: bool,
     pub activation_min_x: f32, // 327
@@ -305,12 +324,174 @@ pub struct GameState {
     pub mode: Mode,
     pub goal_center: Vector2,  // cerca del sprite prize
     pub goal_radius: f32,  
+
+    pub elapsed_time: f32,
+    pub new_record: bool,
+
+    pub hud_message: Option<(String, f32)>, // (texto, segundos restantes)
+
+    pub fog: FogOfWar,
+
+    pub health: f32,
+    pub contact_damage: ContactDamage,
+    pub game_over_time: f32, // segundos desde que terminó la partida (jump-scare)
+    pub game_over_title: &'static str,
+    pub killer: Option<usize>, // tipo de la criatura del jump-scare
+
+    pub inventory: Inventory,
+
+    pub objectives: Vec<Objective>,
+    pub runes_total: u32,
+}
+
+impl GameState {
+    pub fn new(level: &Level) -> Self {
+        GameState {
+            flashlight_active: false,
+            activation_min_x: level.activation_min_x,
+            activation_min_y: level.activation_min_y,
+            in_special_zone: false,
+            mode: Mode::Playing,
+            goal_center: level.goal_center,
+            goal_radius: level.goal_radius,
+            elapsed_time: 0.0,
+            new_record: false,
+            hud_message: None,
+            fog: FogOfWar::new(&level.maze, level.fog_enabled),
+            health: MAX_HEALTH,
+            contact_damage: level.contact_damage,
+            game_over_time: 0.0,
+            game_over_title: "HAS MUERTO",
+            killer: None,
+            inventory: Inventory::default(),
+            objectives: level.objectives.clone(),
+            runes_total: level.runes_total,
+        }
+    }
+
+    pub fn at_exit(&self, pos: Vector2) -> bool {
+        let dx = pos.x - self.goal_center.x;
+        let dy = pos.y - self.goal_center.y;
+        dx * dx + dy * dy <= self.goal_radius * self.goal_radius
+    }
+
+    pub fn objective_context(&self, pos: Vector2, archetypes: &Archetypes) -> ObjectiveContext {
+        ObjectiveContext {
+            at_exit: self.at_exit(pos),
+            gems: self.inventory.tally(Tally::Gems, archetypes),
+            runes: self.inventory.tally(Tally::Runes, archetypes),
+            runes_total: self.runes_total,
+            elapsed_time: self.elapsed_time,
+        }
+    }
+
+    pub fn zone_name(&self, pos: Vector2) -> &'static str {
+        if self.at_exit(pos) {
+            "meta"
+        } else if self.in_special_zone {
+            "especial"
+        } else {
+            "normal"
+        }
+    }
+
+    pub fn show_message(&mut self, text: &str) {
+        self.hud_message = Some((text.to_string(), 2.5));
+    }
+}
+
+// Devuelve jugador, sprites, zonas y audio al estado inicial del nivel
+fn restart_level(
+    level: &mut Level,
+    player: &mut Player,
+    sprites: &mut Vec<Sprite>,
+    game_state: &mut GameState,
+    audio_system: &mut AudioSystem,
+    background_music: Option<&Sound>,
+    zone_music: Option<&Sound>,
+) {
+    level.close_doors();
+    *player = level.spawn_player(player.fov);
+    *sprites = level.spawn_sprites();
+    *game_state = GameState::new(level);
+    audio_system.reset(background_music, zone_music);
+    log_debug!("Nivel {} reiniciado", level.path);
+}
+
+fn save_game(path: &str, level: &Level, player: &Player, sprites: &[Sprite], game_state: &mut GameState) {
+    match SaveData::capture(level, player, sprites, game_state).write(path) {
+        Ok(()) => {
+            log_debug!("Partida guardada en {}", path);
+            game_state.show_message("Partida guardada");
+        }
+        Err(e) => {
+            log_error!("Error al guardar: {}", e);
+            game_state.show_message("No se pudo guardar la partida");
+        }
+    }
+}
+
+// Carga una partida; si es de otro nivel, ese nivel reemplaza al actual
+fn load_game(
+    path: &str,
+    level: &mut Level,
+    player: &mut Player,
+    sprites: &mut Vec<Sprite>,
+    game_state: &mut GameState,
+    archetypes: &Archetypes,
+    block_size: usize,
+) -> Result<(), String> {
+    let data = SaveData::read(path)?;
+
+    if data.level_path != level.path {
+        if !std::path::Path::new(&data.level_path).exists() {
+            return Err(format!("no existe el nivel {}", data.level_path));
+        }
+        let mut new_level = Level::load(&data.level_path, archetypes, block_size)?;
+        data.apply(&mut new_level, player, sprites, game_state)?;
+        *level = new_level;
+    } else {
+        data.apply(level, player, sprites, game_state)?;
+    }
+
+    log_debug!("Partida cargada desde {}", path);
+    game_state.show_message("Partida cargada");
+    Ok(())
 }
 
 fn main() {
-    let window_width = 1000;
-    let window_height =800;
-    let block_size = 80;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let config = match Config::from_args(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error de configuración: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = logger::init(config.log_level, config.log_file.as_deref()) {
+        eprintln!("Error de configuración: {}", e);
+        std::process::exit(2);
+    }
+    log_info!("Nivel: {}  Semilla: {}", config.level, config.seed);
+
+    // Lista de recursos; lo que falte se reemplaza y se informa. Si falta la lista o los tipos de sprite
+    // se usan las copias incluidas en el ejecutable.
+    let mut report = AssetReport::default();
+    let manifest = report.check(MANIFEST_FILE, Manifest::load(MANIFEST_FILE)).unwrap_or_else(Manifest::builtin);
+
+    // Tipos de sprite: hoja, sonido, tamaño y comportamiento de cada uno
+    let archetypes =
+        report.check(&manifest.sprites, Archetypes::load(&manifest.sprites)).unwrap_or_else(Archetypes::builtin);
+
+    let window_width = config.window_width;
+    let window_height = config.window_height;
+    let block_size = config.block_size;
 
     let (mut window, raylib_thread) = raylib::init()
         .size(window_width, window_height)
@@ -318,80 +499,204 @@ fn main() {
         .vsync()
         .build();
 
-    window.set_target_fps(60);
+    let target_fps = 60;
+    window.set_target_fps(target_fps);
 
-    let internal_width = 500;  
-    let internal_height = 400;
+    let internal_width = config.internal_width;
+    let internal_height = config.internal_height;
 
 
     //Load Music once before the loop
     let mut audio = RaylibAudio::init_audio_device();
-    let mut audio_system = AudioSystem::new(&mut audio);
+    let (music_volume, sfx_volume) = if config.mute {
+        (0.0, 0.0)
+    } else {
+        (config.music_volume, config.sfx_volume)
+    };
+    let mut audio_system = AudioSystem::new(&mut audio, music_volume, sfx_volume);
    
-    let background_music = Sound::load_sound("assets/sounds/music1.mp3").expect("No se pudo cargar la música");
-    let zone_music = Sound::load_sound("assets/sounds/music2.mp3").expect("No se pudo cargar la música");
+    let mut load_music = |name: &str| {
+        let path = manifest.music(name)?;
+        report.check(path, Sound::load_sound(path))
+    };
+    let background_music = load_music("background");
+    let zone_music = load_music("zone");
    
     //Efectos especiales
-    audio_system.load_sound("creature_whisper", "assets/sounds/creature.mp3");
-    audio_system.load_sound("shimmering", "assets/sounds/shimmering.mp3");
-    //audio_system.load_sound("success", "assets/sounds/jingle.mp3");
+    // Sonidos de proximidad y de jump-scare de los tipos de sprite, identificados por su archivo
+    for (_, archetype) in archetypes.iter() {
+        for path in archetype.sound.iter().chain(&archetype.scare_sound) {
+            if !audio_system.sounds.contains_key(path) {
+                report.check(path, audio_system.load_sound(path, path));
+            }
+        }
+    }
+    for (key, path) in &manifest.sounds {
+        report.check(path, audio_system.load_sound(key, path));
+    }
 
     //Iniciar musica
-    audio_system.audio.play_sound(&background_music);
-
-    let mut game_state = GameState {
-        flashlight_active: false,
-        activation_min_x: 327.0,
-        activation_min_y: 160.0,
-        in_special_zone: false,
-        mode: Mode::Playing,
-        goal_center: Vector2::new(850.0, 875.0), // objetivo
-        goal_radius: 140.0,
+    audio_system.play_music(background_music.as_ref());
+
+    // Load the level once before the loop
+    let mut level = match Level::load(&config.level, &archetypes, block_size) {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("Error al cargar el nivel: {}", e);
+            std::process::exit(2);
+        }
     };
+
+    let mut game_state = GameState::new(&level);
+
+    // Tiempos: par del nivel y mejores marcas guardadas
+    let mut level_hash = level.hash();
+    let mut par_time = level.par_time(block_size, target_fps as f32);
+    let mut records = Records::load(RECORDS_FILE);
     
     let background_color = Color::BLACK;
     let mut framebuffer = Framebuffer::new(internal_width as u32, internal_height as u32, background_color);
+    framebuffer.set_pixel_depth(config.pixel_depth);
 
-    // Framebuffer para el mapa
-    let mut fb_map = Framebuffer::new(150, 130, background_color);
-    let map_block_size = 10; // Tamaño más pequeño para el mapa
-
-    // Load the maze once before the loop
-    let maze = load_maze("maze.txt");
+    // Minimapa
+    let mut map_overlay = MapOverlay::new();
+    let mut minimap = Minimap::new(150, 130, config.minimap_mode, config.minimap_rotate, config.minimap_rays);
 
     //Load player
-    let mut player = Player{pos: Vector2::new(150.0,150.0), a: PI/3.0, fov:PI/3.0};
+    let mut player = level.spawn_player(config.fov);
 
     //Load textures
-    let texture_cache = TextureManager::new(&mut window, &raylib_thread);
+    let mut texture_cache = TextureManager::new(&mut window, &raylib_thread, &manifest, &archetypes, &mut report);
+
+    for decal in &level.decals {
+        report.check(&decal.texture, texture_cache.load_decal(&decal.texture));
+    }
+
+    // Informe de recursos: detalle en el log y un aviso en pantalla si faltó algo
+    report.log();
+    if !report.failed.is_empty() {
+        game_state.show_message(&format!("Faltan {} recursos (ver el log)", report.failed.len()));
+    }
 
     //Crear sprites
-    let mut sprites = vec![
-        Sprite::new(500.0, 100.0, 'C', 4, SpriteType::creature), 
-        Sprite::new(850.0, 875.0, 'P', 1, SpriteType::prize),
-    ];
+    let mut sprites = level.spawn_sprites();
+    let mut rng = Rng::new(config.seed);
+    let mut spatial = SpatialIndex::new(block_size);
+    let mut particles = ParticleSystem::for_level(&level, &archetypes);
+    // Textura del estallido de la meta: la hoja del tipo que indica el manifiesto
+    let goal_burst_texture = manifest.goal_burst.as_deref().and_then(|name| archetypes.find(name));
+    // Generador aparte para los efectos: así no alteran la secuencia que usa la IA
+    let mut fx_rng = Rng::new(config.seed.rotate_left(32));
 
     let sky_color = Color::new(126, 104, 166, 255);
     let floor_color = Color::new(45, 38, 59, 255);
 
-    let mut frame_count = 0;
-    let mut last_time = std::time::Instant::now();
+    let mut debug_overlay = DebugOverlay::new(config.debug_overlay);
     let mut last_frame_time = std::time::Instant::now();
 
 
     while !window.window_should_close() {
 
+        if window.is_key_pressed(KeyboardKey::KEY_F3) {
+            debug_overlay.toggle();
+        }
+
+        // Minimapa: ajustar / seguir y rotación con el rumbo
+        if window.is_key_pressed(KeyboardKey::KEY_V) {
+            minimap.toggle_mode();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_N) {
+            minimap.rotate = !minimap.rotate;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_LEFT_BRACKET) {
+            minimap.rays = minimap.rays.saturating_sub(1);
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_RIGHT_BRACKET) {
+            minimap.rays = (minimap.rays + 1).min(MAX_MINIMAP_RAYS);
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_TAB) {
+            map_overlay.toggle();
+        }
+
+        // Pausa y reinicio
+        if window.is_key_pressed(KeyboardKey::KEY_P) {
+            game_state.mode = match game_state.mode {
+                Mode::Playing => Mode::Paused,
+                Mode::Paused => Mode::Playing,
+                other => other,
+            };
+        }
+
+        if game_state.mode != Mode::Playing && window.is_key_pressed(KeyboardKey::KEY_R) {
+            restart_level(
+                &mut level,
+                &mut player,
+                &mut sprites,
+                &mut game_state,
+                &mut audio_system,
+                background_music.as_ref(),
+                zone_music.as_ref(),
+            );
+            particles = ParticleSystem::for_level(&level, &archetypes);
+        }
+
+        // Guardar / cargar: desde el menú de pausa o con F5 / F9; sólo se guarda una partida en curso
+        let paused = game_state.mode == Mode::Paused;
+        let can_save = paused || game_state.mode == Mode::Playing;
+        let save_path = if can_save && window.is_key_pressed(KeyboardKey::KEY_F5) {
+            Some(QUICKSAVE_FILE)
+        } else if paused && window.is_key_pressed(KeyboardKey::KEY_G) {
+            Some(SAVE_FILE)
+        } else {
+            None
+        };
+        if let Some(path) = save_path {
+            save_game(path, &level, &player, &sprites, &mut game_state);
+        }
+
+        let load_path = if window.is_key_pressed(KeyboardKey::KEY_F9) {
+            Some(QUICKSAVE_FILE)
+        } else if paused && window.is_key_pressed(KeyboardKey::KEY_C) {
+            Some(SAVE_FILE)
+        } else {
+            None
+        };
+        if let Some(path) = load_path {
+            match load_game(path, &mut level, &mut player, &mut sprites, &mut game_state, &archetypes, block_size) {
+                Ok(()) => {
+                    level_hash = level.hash();
+                    par_time = level.par_time(block_size, target_fps as f32);
+                    audio_system.reset(background_music.as_ref(), zone_music.as_ref());
+                    for decal in &level.decals {
+                        if let Err(e) = texture_cache.load_decal(&decal.texture) {
+                            log_warn!("No se pudo cargar el decal {}: {}; se usa un tablero", decal.texture, e);
+                        }
+                    }
+                    particles = ParticleSystem::for_level(&level, &archetypes);
+                }
+                Err(e) => {
+                    log_error!("Error al cargar: {}", e);
+                    game_state.show_message("No se pudo cargar la partida");
+                }
+            }
+        }
+
+        // Los sprites pueden haber cambiado (reinicio, carga): se reindexan antes de consultarlos
+        spatial.rebuild(&level.maze, &sprites);
+
         game_state.in_special_zone = player.pos.x >= game_state.activation_min_x 
                           && player.pos.y >= game_state.activation_min_y;
         
         game_state.flashlight_active = game_state.in_special_zone;
 
         //Inicializar funciones de musica
-        audio_system.play_proximity_sounds(player.pos, &sprites);
+        if game_state.mode != Mode::GameOver {
+            audio_system.play_proximity_sounds(player.pos, &sprites, &spatial);
+        }
         audio_system.update_zone_music(
             game_state.in_special_zone,
-            &background_music,
-            &zone_music
+            background_music.as_ref(),
+            zone_music.as_ref(),
         );
 
         // Calcular delta time
@@ -399,27 +704,94 @@ fn main() {
         let delta_time = current_time.duration_since(last_frame_time).as_secs_f32();
         last_frame_time = current_time;
 
-        // Actualizar sprite
-        for sprite in &mut sprites {
-            sprite.update(delta_time);
+        if map_overlay.visible {
+            map_overlay.handle_input(&window, delta_time);
+        }
+
+        if let Some((_, remaining)) = &mut game_state.hud_message {
+            *remaining -= delta_time;
+            if *remaining <= 0.0 {
+                game_state.hud_message = None;
+            }
+        }
+
+        // Actualizar sprite (congelados durante la pausa)
+        if game_state.mode != Mode::Paused {
+            for sprite in &mut sprites {
+                sprite.update(delta_time, texture_cache.get_clips(sprite.archetype));
+            }
+
+            particles.update_dust(player.pos, game_state.in_special_zone);
+            particles.update(delta_time, &mut fx_rng);
         }
         
         // Clear framebuffer
         framebuffer.clear();
-        fb_map.clear();
 
         //Procesar eventos
         if game_state.mode == Mode::Playing {
-            process_events(&window, &mut player, block_size, &maze);
+            // Chocar con una puerta la abre si se tiene la llave de su color
+            if let Some(cell) = process_events(&window, &mut player, block_size, &level.maze)
+                && let Some(color) = level.door_color(cell)
+            {
+                if game_state.inventory.has_key(color, &archetypes) {
+                    level.open_door(cell);
+                    game_state.show_message(&format!("Puerta {} abierta", color.name()));
+                    log_debug!("Puerta {} abierta en {:?}", color.name(), cell);
+                } else {
+                    game_state.show_message(&format!("Puerta cerrada: necesitas la llave {}", color.name()));
+                }
+            }
+        }
+
+        if game_state.mode == Mode::GameOver {
+            game_state.game_over_time += delta_time;
         }
 
         if game_state.mode == Mode::Playing {
-            let dx = player.pos.x - game_state.goal_center.x;
-            let dy = player.pos.y - game_state.goal_center.y;
-            let dist2 = dx*dx + dy*dy;
+            game_state.elapsed_time += delta_time;
 
-            if dist2 <= game_state.goal_radius * game_state.goal_radius {
+            ai::update_creatures(&mut sprites, &player, &level.maze, block_size, delta_time, &mut rng);
+            spatial.rebuild(&level.maze, &sprites);
+
+            for id in inventory::collect_items(&mut sprites, &player, &spatial, &mut game_state.inventory) {
+                let archetype = archetypes.get(id);
+                audio_system.play_effect("pickup");
+                game_state.show_message(&format!("Recogiste: {}", archetype.label.to_lowercase()));
+                log_debug!("Recogido {} en {:.0},{:.0}", archetype.name, player.pos.x, player.pos.y);
+            }
+
+            // El contacto con una criatura quita vida; sin vida, se acaba la partida
+            if let Some(creature) = ai::creature_contact(&sprites, &player, &spatial) {
+                game_state.health = game_state.contact_damage.apply(game_state.health, delta_time);
+                if game_state.health <= 0.0 {
+                    game_state.health = 0.0;
+                    game_state.mode = Mode::GameOver;
+                    game_state.killer = Some(creature);
+                    game_state.game_over_time = 0.0;
+                    audio_system.stop_effects();
+                    if let Some(scare) = &archetypes.get(creature).scare_sound {
+                        audio_system.play_effect(scare);
+                    }
+                }
+            }
+
+            // Se gana al cumplir todos los objetivos obligatorios; un objetivo con tiempo vencido hace perder
+            let progress = objectives::evaluate(&game_state.objectives, &game_state.objective_context(player.pos, &archetypes));
+            if progress == Progress::Failed && game_state.mode == Mode::Playing {
+                game_state.mode = Mode::GameOver;
+                game_state.game_over_title = "SE ACABÓ EL TIEMPO";
+                game_state.game_over_time = JUMPSCARE_DURATION; // sin jump-scare
+                audio_system.stop_effects();
+            } else if progress == Progress::Done && game_state.mode == Mode::Playing {
                 game_state.mode = Mode::Success;
+                particles.burst(EmitterConfig::goal_burst(goal_burst_texture), game_state.goal_center, 150, &mut fx_rng);
+                game_state.new_record = records.submit(level_hash, game_state.elapsed_time);
+                if game_state.new_record
+                    && let Err(e) = records.save()
+                {
+                    log_error!("No se pudo guardar {}: {}", RECORDS_FILE, e);
+                }
             }
         }
 
@@ -436,26 +808,50 @@ fn main() {
             }
         }
 
-        render_3D(&mut framebuffer, &maze, block_size, &player, &texture_cache, &game_state);
+        let stage_start = Instant::now();
+        render_3D(&mut framebuffer, &level.maze, block_size, &player, &texture_cache, &mut game_state);
+        decals::render_decals(&mut framebuffer, &player, &level.decals, block_size, &texture_cache);
+        debug_overlay.record(Stage::Raycast, stage_start.elapsed());
 
         // Renderizar sprites
-        render_sprites(&mut framebuffer, &player, &sprites, &texture_cache);
+        let stage_start = Instant::now();
+        render_sprites(
+            &mut framebuffer,
+            &player,
+            &sprites,
+            &particles,
+            &spatial,
+            &game_state.fog,
+            &texture_cache,
+        );
+        if game_state.flashlight_active {
+            render_flashlight(&mut framebuffer);
+        }
+        debug_overlay.record(Stage::Sprites, stage_start.elapsed());
+        debug_overlay.particles = particles.count();
         
         // Renderizar mapa
-        render_maze(&mut fb_map, &maze, map_block_size, &player);
-
-        // Dibujar al jugador en el mini mapa
-        let player_map_x = (player.pos.x / block_size as f32 * map_block_size as f32) as u32;
-        let player_map_y = (player.pos.y / block_size as f32 * map_block_size as f32) as u32;
-
-        fb_map.set_current_color(Color::VIOLET);
-        // Dibujar una cruz para mayor visibilidad
-        for offset in -1..=1 {
-            fb_map.set_pixel(player_map_x.saturating_add_signed(offset), player_map_y);
-            fb_map.set_pixel(player_map_x, player_map_y.saturating_add_signed(offset));
+        let stage_start = Instant::now();
+        let scene = MapScene {
+            maze: &level.maze,
+            block_size,
+            player: &player,
+            sprites: &sprites,
+            archetypes: &archetypes,
+            game_state: &game_state,
+        };
+        if map_overlay.visible {
+            map_overlay.render(&mut framebuffer, &scene);
+        } else {
+            minimap.render(&scene);
         }
+        debug_overlay.record(Stage::Minimap, stage_start.elapsed());
+
+        let zone = game_state.zone_name(player.pos);
+        let fps = window.get_fps();
 
         // Swap buffers
+        let stage_start = Instant::now();
         framebuffer.swap_buffers(&mut window, &raylib_thread);
 
         {
@@ -463,54 +859,185 @@ fn main() {
             let mut d = window.begin_drawing(&raylib_thread);
             d.draw_fps(10, 10);
 
-            let map_scale = 1.0;
-            let map_display_width = (fb_map.width as f32 * map_scale) as i32;
-            let map_display_height = (fb_map.height as f32 * map_scale) as i32;
-            let map_x = window_width - map_display_width - 20;
-            let map_y = 20;
-
-            d.draw_rectangle(
-                map_x - 5, map_y - 5, 
-                map_display_width + 10, map_display_height + 10,
-                Color::new(0, 0, 0, 180) // Negro semitransparente
-            );
+            // Cronómetro
+            let timer_text = match par_time {
+                Some(par) => format!("{}  (par {})", format_time(game_state.elapsed_time), format_time(par)),
+                None => format_time(game_state.elapsed_time),
+            };
+            d.draw_text(&timer_text, 10, 35, 20, Color::RAYWHITE);
+
+            // Barra de vida
+            let bar_width = 200;
+            let health_width = (bar_width as f32 * game_state.health / MAX_HEALTH) as i32;
+            let bar_y = window_height - 30;
+            d.draw_rectangle(10, bar_y, bar_width, 14, Color::new(0, 0, 0, 180));
+            d.draw_rectangle(10, bar_y, health_width, 14, Color::new(180, 30, 40, 255));
+            d.draw_rectangle_lines(10, bar_y, bar_width, 14, Color::RAYWHITE);
+
+            // Inventario: icono y cantidad de cada objeto, encima de la barra de vida
+            // (el icono es la hoja del primer tipo que da cada objeto)
+            for (i, (item, id)) in archetypes.items().into_iter().enumerate() {
+                let count = game_state.inventory.count(item);
+                let x = 10 + i as i32 * 70;
+                let y = bar_y - 40;
+                if let Some(sheet) = texture_cache.get_sprite_sheet(id) {
+                    let dest = Rectangle::new(x as f32, y as f32, 32.0, 32.0);
+                    d.draw_texture_pro(&sheet.texture, sheet.frame_rect(0, 0), dest, Vector2::zero(), 0.0, Color::WHITE);
+                }
+                let color = if count > 0 { Color::RAYWHITE } else { Color::GRAY };
+                d.draw_text(&format!("x{}", count), x + 36, y + 8, 20, color);
+            }
 
-            // Dibujar el mapa
-            for y in 0..fb_map.height {
-                for x in 0..fb_map.width {
-                    let index = (y * fb_map.width + x) as usize;
-                    if index < fb_map.color_buffer.len() {
-                        let color = fb_map.color_buffer[index];
-                        if color != fb_map.background_color {
-                            d.draw_rectangle(
-                                map_x + x as i32,
-                                map_y + y as i32,
-                                1, 1, // Dibujar pixel por pixel
-                                color
-                            );
+            if map_overlay.visible {
+                map_overlay.draw_legend(&mut d, &archetypes, window_width - 220, 20);
+            } else {
+                let map_scale = 1.0;
+                let map_display_width = (minimap.framebuffer.width as f32 * map_scale) as i32;
+                let map_display_height = (minimap.framebuffer.height as f32 * map_scale) as i32;
+                let map_x = window_width - map_display_width - 20;
+                let map_y = 20;
+
+                d.draw_rectangle(
+                    map_x - 5, map_y - 5, 
+                    map_display_width + 10, map_display_height + 10,
+                    Color::new(0, 0, 0, 180) // Negro semitransparente
+                );
+
+                // Dibujar el mapa
+                for y in 0..minimap.framebuffer.height {
+                    for x in 0..minimap.framebuffer.width {
+                        let index = (y * minimap.framebuffer.width + x) as usize;
+                        if index < minimap.framebuffer.color_buffer.len() {
+                            let color = minimap.framebuffer.color_buffer[index];
+                            if color != minimap.framebuffer.background_color {
+                                d.draw_rectangle(
+                                    map_x + x as i32,
+                                    map_y + y as i32,
+                                    1, 1, // Dibujar pixel por pixel
+                                    color
+                                );
+                            }
                         }
                     }
                 }
             }
+
+            // Objetivos del nivel, debajo del minimapa
+            if !map_overlay.visible {
+                let ctx = game_state.objective_context(player.pos, &archetypes);
+                let lines: Vec<(String, Color)> = game_state
+                    .objectives
+                    .iter()
+                    .map(|objective| {
+                        let (mark, color) = match objective.progress(&ctx) {
+                            Progress::Done => ("[x]", Color::GREEN),
+                            Progress::Failed => ("[!]", Color::RED),
+                            Progress::Pending if objective.required => ("[ ]", Color::RAYWHITE),
+                            Progress::Pending => ("[ ]", Color::LIGHTGRAY),
+                        };
+                        (format!("{} {}", mark, objective.describe(&ctx)), color)
+                    })
+                    .collect();
+
+                // Alineados a la derecha, como el minimapa
+                let widest = lines.iter().map(|(text, _)| measure_text(text, 18)).max().unwrap_or(0);
+                let x = window_width - widest.max(measure_text("Objetivos", 20)) - 20;
+                let mut y = minimap.framebuffer.height as i32 + 40;
+                d.draw_text("Objetivos", x, y, 20, Color::RAYWHITE);
+                for (text, color) in &lines {
+                    y += 22;
+                    d.draw_text(text, x, y, 18, *color);
+                }
+            }
+
             if game_state.mode == Mode::Success {
                 // Cubrir toda la ventana con un fondo semitransparente
                 d.draw_rectangle(0, 0, window_width, window_height, Color::new(0, 0, 0, 200));
 
                 // Texto centrado
                 let tw = measure_text("EXITO", 40);
-                let tw2 = measure_text("Has llegado a la meta.", 20);
+                let tw2 = measure_text("Cumpliste todos los objetivos.", 20);
 
                 d.draw_text("¡EXITO!", (window_width - tw) / 2, window_height / 2 - 40, 40, Color::RAYWHITE);
-                d.draw_text("Has llegado a la meta.", (window_width - tw2) / 2, window_height / 2 + 10, 20, Color::LIGHTGRAY);
+                d.draw_text("Cumpliste todos los objetivos.", (window_width - tw2) / 2, window_height / 2 + 10, 20, Color::LIGHTGRAY);
+
+                // Tiempo de la partida, par y mejor marca
+                let time_text = format!("Tiempo: {}", format_time(game_state.elapsed_time));
+                let par_text = match par_time {
+                    Some(par) => format!("Par: {}", format_time(par)),
+                    None => "Par: --".to_string(),
+                };
+                let best_text = match records.best_time(level_hash) {
+                    Some(best) if game_state.new_record => format!("¡Nuevo récord! {}", format_time(best)),
+                    Some(best) => format!("Mejor tiempo: {}", format_time(best)),
+                    None => "Mejor tiempo: --".to_string(),
+                };
+
+                for (i, text) in [time_text, par_text, best_text].iter().enumerate() {
+                    let tw = measure_text(text, 20);
+                    d.draw_text(text, (window_width - tw) / 2, window_height / 2 + 50 + i as i32 * 25, 20, Color::LIGHTGRAY);
+                }
+
+                let tw3 = measure_text("[R] Jugar de nuevo", 20);
+                d.draw_text("[R] Jugar de nuevo", (window_width - tw3) / 2, window_height / 2 + 135, 20, Color::LIGHTGRAY);
+            }
+
+            if game_state.mode == Mode::GameOver {
+                if game_state.game_over_time < JUMPSCARE_DURATION {
+                    // Jump-scare: la criatura a pantalla completa, temblando y teñida de rojo
+                    if let Some(sheet) = game_state.killer.and_then(|id| texture_cache.get_sprite_sheet(id)) {
+                        let shake = (game_state.game_over_time * 60.0).sin() * 12.0;
+                        let size = window_height as f32 * (1.0 + game_state.game_over_time * 0.4);
+
+                        d.draw_rectangle(0, 0, window_width, window_height, Color::BLACK);
+                        // De frente (fila 0)
+                        d.draw_texture_pro(
+                            &sheet.texture,
+                            sheet.frame_rect(0, 0),
+                            Rectangle::new(
+                                (window_width as f32 - size) / 2.0 + shake,
+                                (window_height as f32 - size) / 2.0,
+                                size,
+                                size,
+                            ),
+                            Vector2::zero(),
+                            0.0,
+                            Color::new(255, 120, 120, 255),
+                        );
+                    }
+                } else {
+                    d.draw_rectangle(0, 0, window_width, window_height, Color::new(40, 0, 0, 220));
+
+                    let title = game_state.game_over_title;
+                    let tw = measure_text(title, 40);
+                    let tw2 = measure_text("[R] Reintentar", 20);
+
+                    d.draw_text(title, (window_width - tw) / 2, window_height / 2 - 40, 40, Color::RED);
+                    d.draw_text("[R] Reintentar", (window_width - tw2) / 2, window_height / 2 + 10, 20, Color::LIGHTGRAY);
+                }
+            }
+
+            if game_state.mode == Mode::Paused {
+                d.draw_rectangle(0, 0, window_width, window_height, Color::new(0, 0, 0, 160));
+
+                let tw = measure_text("PAUSA", 40);
+                let tw2 = measure_text("[P] Continuar   [R] Reiniciar", 20);
+                let tw3 = measure_text("[G] Guardar   [C] Cargar", 20);
+
+                d.draw_text("PAUSA", (window_width - tw) / 2, window_height / 2 - 40, 40, Color::RAYWHITE);
+                d.draw_text("[P] Continuar   [R] Reiniciar", (window_width - tw2) / 2, window_height / 2 + 10, 20, Color::LIGHTGRAY);
+                d.draw_text("[G] Guardar   [C] Cargar", (window_width - tw3) / 2, window_height / 2 + 40, 20, Color::LIGHTGRAY);
+            }
+
+            // Mensajes temporales (guardado, carga...)
+            if let Some((text, _)) = &game_state.hud_message {
+                let tw = measure_text(text, 20);
+                d.draw_text(text, (window_width - tw) / 2, window_height - 60, 20, Color::RAYWHITE);
             }
-        }
 
-        frame_count += 1;
-        if last_time.elapsed().as_secs() >= 1 {
-            frame_count = 0;
-            last_time = std::time::Instant::now();
+            debug_overlay.draw(&mut d, &player, &level.maze, block_size, zone, fps);
         }
-        println!("Posición: {}, {}", player.pos.x, player.pos.y);
+        debug_overlay.record(Stage::Present, stage_start.elapsed());
 
     }
 }
@@ -13,3 +13,10 @@ pub fn load_maze(filename: &str) -> Vec<Vec<char>> {
         .collect()
 }
 
+// Glifo en (x, y); las filas pueden tener largos distintos, lo que queda fuera devuelve None
+pub fn cell_at(maze: &Maze, x: i32, y: i32) -> Option<char> {
+    if x < 0 || y < 0 {
+        return None;
+    }
+    maze.get(y as usize).and_then(|row| row.get(x as usize)).copied()
+}
@@ -0,0 +1,351 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::caster::cast_ray;
+use crate::doors::KeyColor;
+use crate::framebuffer::Framebuffer;
+use crate::maze::{Maze, cell_at};
+use crate::pathfinding::shortest_path;
+use crate::player::Player;
+use crate::archetypes::Archetypes;
+use crate::sprites::Sprite;
+use crate::GameState;
+
+const WALL_COLOR: Color = Color::new(48, 35, 61, 255);
+const OVERLAY_BACKGROUND: Color = Color::new(12, 9, 18, 255);
+const PATH_COLOR: Color = Color::new(90, 200, 255, 255);
+const SPECIAL_ZONE_COLOR: Color = Color::new(58, 30, 72, 255);
+const GOAL_COLOR: Color = Color::new(30, 90, 50, 255);
+const FOV_COLOR: Color = Color::new(200, 190, 120, 255);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinimapMode {
+    Fit,    // el laberinto completo escalado al recuadro
+    Follow, // escala fija centrada en el jugador
+}
+
+// Transformación entre coordenadas del mundo y píxeles del mapa
+pub struct MapView {
+    pub center: Vector2, // punto del mundo que queda en el centro del mapa
+    pub scale: f32,      // píxeles del mapa por unidad del mundo
+    pub rotation: f32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MapView {
+    pub fn world_to_map(&self, p: Vector2) -> Vector2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let vx = p.x - self.center.x;
+        let vy = p.y - self.center.y;
+
+        Vector2::new(
+            (vx * cos - vy * sin) * self.scale + self.width as f32 / 2.0,
+            (vx * sin + vy * cos) * self.scale + self.height as f32 / 2.0,
+        )
+    }
+
+    pub fn map_to_world(&self, x: f32, y: f32) -> Vector2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let rx = (x - self.width as f32 / 2.0) / self.scale;
+        let ry = (y - self.height as f32 / 2.0) / self.scale;
+
+        Vector2::new(
+            rx * cos + ry * sin + self.center.x,
+            -rx * sin + ry * cos + self.center.y,
+        )
+    }
+}
+
+// Lo que el mapa muestra en un frame, igual para el minimapa y el mapa completo
+#[derive(Clone, Copy)]
+pub struct MapScene<'a> {
+    pub maze: &'a Maze,
+    pub block_size: usize,
+    pub player: &'a Player,
+    pub sprites: &'a [Sprite],
+    pub archetypes: &'a Archetypes,
+    pub game_state: &'a GameState,
+}
+
+pub struct Minimap {
+    pub framebuffer: Framebuffer,
+    pub mode: MinimapMode,
+    pub rotate: bool, // con el rumbo del jugador siempre hacia arriba
+    pub follow_block_size: f32, // píxeles por celda en modo Follow
+    pub rays: usize,            // rayos del caster trazados sobre el mapa
+}
+
+impl Minimap {
+    pub fn new(width: u32, height: u32, mode: MinimapMode, rotate: bool, rays: usize) -> Self {
+        Minimap {
+            framebuffer: Framebuffer::new(width, height, Color::BLACK),
+            mode,
+            rotate,
+            follow_block_size: 10.0,
+            rays,
+        }
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            MinimapMode::Fit => MinimapMode::Follow,
+            MinimapMode::Follow => MinimapMode::Fit,
+        };
+    }
+
+    pub fn view(&self, maze: &Maze, block_size: usize, player: &Player) -> MapView {
+        let width = self.framebuffer.width;
+        let height = self.framebuffer.height;
+        let rotation = if self.rotate { -PI / 2.0 - player.a } else { 0.0 };
+
+        match self.mode {
+            MinimapMode::Fit => {
+                let cols = maze.iter().map(|row| row.len()).max().unwrap_or(1) as f32;
+                let rows = maze.len().max(1) as f32;
+                let maze_w = cols * block_size as f32;
+                let maze_h = rows * block_size as f32;
+
+                // Rotando, el laberinto tiene que caber en cualquier orientación: se usa la diagonal
+                let scale = if self.rotate {
+                    width.min(height) as f32 / maze_w.hypot(maze_h)
+                } else {
+                    (width as f32 / maze_w).min(height as f32 / maze_h)
+                };
+
+                MapView { center: Vector2::new(maze_w / 2.0, maze_h / 2.0), scale, rotation, width, height }
+            }
+            MinimapMode::Follow => MapView {
+                center: player.pos,
+                scale: self.follow_block_size / block_size as f32,
+                rotation,
+                width,
+                height,
+            },
+        }
+    }
+
+    pub fn render(&mut self, scene: &MapScene) {
+        let view = self.view(scene.maze, scene.block_size, scene.player);
+        self.framebuffer.clear();
+        render_maze(&mut self.framebuffer, scene, &view, self.rays);
+    }
+}
+
+// Color de cada glifo de pared (el mismo en el minimapa, el mapa completo y su leyenda)
+fn glyph_color(cell: char) -> Color {
+    match cell {
+        '+' => Color::new(78, 70, 92, 255),   // piedra
+        '-' | '|' => Color::new(92, 58, 96, 255), // flores
+        'l' => Color::new(96, 72, 52, 255),   // muro
+        '_' => Color::new(52, 66, 112, 255),  // runas
+        '#' => Color::new(44, 86, 52, 255),   // hiedra
+        'R' | 'G' | 'B' => KeyColor::from_glyph(cell).map_or(WALL_COLOR, |c| c.color()), // puertas
+        _ => WALL_COLOR,
+    }
+}
+
+const LEGEND_GLYPHS: [(&str, char); 8] = [
+    ("+  piedra", '+'),
+    ("- |  flores", '-'),
+    ("l  muro", 'l'),
+    ("_  runas", '_'),
+    ("#  hiedra", '#'),
+    ("R  puerta roja", 'R'),
+    ("G  puerta verde", 'G'),
+    ("B  puerta azul", 'B'),
+];
+
+pub fn render_maze(framebuffer: &mut Framebuffer, scene: &MapScene, view: &MapView, num_rays: usize) {
+    let MapScene { maze, block_size, player, sprites, archetypes, game_state } = *scene;
+    let goal_r2 = game_state.goal_radius * game_state.goal_radius;
+
+    // Cada píxel del mapa se transforma al mundo y toma el color de lo que hay ahí
+    for y in 0..framebuffer.height {
+        for x in 0..framebuffer.width {
+            let world = view.map_to_world(x as f32 + 0.5, y as f32 + 0.5);
+            let cell_x = (world.x / block_size as f32).floor() as i32;
+            let cell_y = (world.y / block_size as f32).floor() as i32;
+
+            if !game_state.fog.is_revealed(cell_x, cell_y) {
+                continue;
+            }
+
+            let color = match cell_at(maze, cell_x, cell_y) {
+                Some(' ') => {
+                    let dx = world.x - game_state.goal_center.x;
+                    let dy = world.y - game_state.goal_center.y;
+                    if dx * dx + dy * dy <= goal_r2 {
+                        GOAL_COLOR
+                    } else if world.x >= game_state.activation_min_x && world.y >= game_state.activation_min_y {
+                        SPECIAL_ZONE_COLOR
+                    } else {
+                        continue;
+                    }
+                }
+                Some(cell) => glyph_color(cell),
+                None => continue,
+            };
+
+            framebuffer.set_current_color(color);
+            framebuffer.set_pixel(x, y);
+        }
+    }
+
+    let player_map = view.world_to_map(player.pos);
+
+    // Cono de visión: sin rayos del caster, solo los bordes a una distancia fija
+    if num_rays == 0 {
+        let cone_length = block_size as f32 * 2.5;
+        framebuffer.set_current_color(FOV_COLOR);
+        for edge in [player.a - player.fov / 2.0, player.a + player.fov / 2.0] {
+            let tip = view.world_to_map(Vector2::new(
+                player.pos.x + edge.cos() * cone_length,
+                player.pos.y + edge.sin() * cone_length,
+            ));
+            framebuffer.draw_line(player_map.x as i32, player_map.y as i32, tip.x as i32, tip.y as i32);
+        }
+    }
+
+    // draw what the player sees: recorrido real de cada rayo hasta la pared que golpea
+    for i in 0..num_rays {
+        let current_ray = if num_rays > 1 { i as f32 / (num_rays - 1) as f32 } else { 0.5 };
+        let a = (player.a - (player.fov / 2.0)) + (player.fov * current_ray);
+        cast_ray(framebuffer, maze, player, block_size, a, Some(view), None);
+    }
+
+    // Marcadores proporcionales al tamaño del mapa
+    let marker = (framebuffer.width.min(framebuffer.height) / 100).max(1) as i32;
+
+    // Meta
+    let goal = view.world_to_map(game_state.goal_center);
+    framebuffer.set_current_color(Color::GREEN);
+    framebuffer.fill_square(goal.x as i32, goal.y as i32, marker);
+
+    // Sprites (solo los que están en celdas descubiertas)
+    let revealed = |p: Vector2| {
+        game_state.fog.is_revealed(
+            (p.x / block_size as f32).floor() as i32,
+            (p.y / block_size as f32).floor() as i32,
+        )
+    };
+    for sprite in sprites.iter().filter(|s| s.is_alive && revealed(s.pos)) {
+        let p = view.world_to_map(sprite.pos);
+        framebuffer.set_current_color(archetypes.get(sprite.archetype).map_color);
+        framebuffer.fill_square(p.x as i32, p.y as i32, marker);
+    }
+
+    // Jugador: una cruz para mayor visibilidad
+    let (px, py) = (player_map.x as i32, player_map.y as i32);
+    framebuffer.set_current_color(Color::VIOLET);
+    for t in -marker / 2..=marker / 2 {
+        framebuffer.draw_line(px - marker, py + t, px + marker, py + t);
+        framebuffer.draw_line(px + t, py - marker, px + t, py + marker);
+    }
+}
+
+// Mapa a pantalla completa con desplazamiento y zoom
+pub struct MapOverlay {
+    pub visible: bool,
+    pub pan: Vector2, // desplazamiento respecto al jugador, en unidades del mundo
+    pub zoom: f32,
+    pub show_path: bool, // camino resuelto hasta la meta (depuración)
+}
+
+impl MapOverlay {
+    const MIN_ZOOM: f32 = 0.5;
+    const MAX_ZOOM: f32 = 8.0;
+
+    pub fn new() -> Self {
+        MapOverlay { visible: false, pan: Vector2::zero(), zoom: 1.0, show_path: false }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn handle_input(&mut self, window: &RaylibHandle, delta_time: f32) {
+        const PAN_SPEED: f32 = 600.0; // unidades del mundo por segundo con zoom 1
+        const ZOOM_SPEED: f32 = 1.5;
+
+        let step = PAN_SPEED * delta_time / self.zoom;
+        if window.is_key_down(KeyboardKey::KEY_A) { self.pan.x -= step; }
+        if window.is_key_down(KeyboardKey::KEY_D) { self.pan.x += step; }
+        if window.is_key_down(KeyboardKey::KEY_W) { self.pan.y -= step; }
+        if window.is_key_down(KeyboardKey::KEY_S) { self.pan.y += step; }
+
+        let mut zoom_factor = 1.0 + window.get_mouse_wheel_move() * 0.1;
+        if window.is_key_down(KeyboardKey::KEY_EQUAL) { zoom_factor *= ZOOM_SPEED.powf(delta_time); }
+        if window.is_key_down(KeyboardKey::KEY_MINUS) { zoom_factor /= ZOOM_SPEED.powf(delta_time); }
+        self.zoom = (self.zoom * zoom_factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+
+        if window.is_key_pressed(KeyboardKey::KEY_H) {
+            self.show_path = !self.show_path;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_ZERO) {
+            self.pan = Vector2::zero();
+            self.zoom = 1.0;
+        }
+    }
+
+    // Con zoom 1 el laberinto completo cabe en pantalla
+    pub fn view(&self, maze: &Maze, block_size: usize, player: &Player, width: u32, height: u32) -> MapView {
+        let cols = maze.iter().map(|row| row.len()).max().unwrap_or(1) as f32;
+        let rows = maze.len().max(1) as f32;
+        let fit = (width as f32 / (cols * block_size as f32)).min(height as f32 / (rows * block_size as f32)) * 0.9;
+
+        MapView {
+            center: Vector2::new(player.pos.x + self.pan.x, player.pos.y + self.pan.y),
+            scale: fit * self.zoom,
+            rotation: 0.0,
+            width,
+            height,
+        }
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, scene: &MapScene) {
+        let MapScene { maze, block_size, player, game_state, .. } = *scene;
+        let view = self.view(maze, block_size, player, framebuffer.width, framebuffer.height);
+        framebuffer.color_buffer.fill(OVERLAY_BACKGROUND);
+        render_maze(framebuffer, scene, &view, 0);
+
+        if self.show_path {
+            let to_cell = |p: Vector2| ((p.x / block_size as f32) as usize, (p.y / block_size as f32) as usize);
+            if let Some(path) = shortest_path(maze, to_cell(player.pos), to_cell(game_state.goal_center)) {
+                let center = |(x, y): (usize, usize)| {
+                    view.world_to_map(Vector2::new(
+                        (x as f32 + 0.5) * block_size as f32,
+                        (y as f32 + 0.5) * block_size as f32,
+                    ))
+                };
+                framebuffer.set_current_color(PATH_COLOR);
+                for pair in path.windows(2) {
+                    let (a, b) = (center(pair[0]), center(pair[1]));
+                    framebuffer.draw_line(a.x as i32, a.y as i32, b.x as i32, b.y as i32);
+                }
+            }
+        }
+    }
+
+    pub fn draw_legend(&self, d: &mut RaylibDrawHandle, archetypes: &Archetypes, x: i32, y: i32) {
+        let mut entries: Vec<(&str, Color)> = LEGEND_GLYPHS.iter().map(|&(label, c)| (label, glyph_color(c))).collect();
+        entries.push(("Jugador", Color::VIOLET));
+        entries.push(("Meta", Color::GREEN));
+        entries.extend(archetypes.iter().map(|(_, a)| (a.label.as_str(), a.map_color)));
+        if self.show_path {
+            entries.push(("Camino a la meta", PATH_COLOR));
+        }
+
+        let line_height = 22;
+        d.draw_rectangle(x - 8, y - 8, 210, entries.len() as i32 * line_height + 40, Color::new(0, 0, 0, 180));
+        for (i, (label, color)) in entries.iter().enumerate() {
+            let ly = y + i as i32 * line_height;
+            d.draw_rectangle(x, ly + 2, 14, 14, *color);
+            d.draw_text(label, x + 22, ly, 18, Color::RAYWHITE);
+        }
+
+        let hint_y = y + entries.len() as i32 * line_height + 4;
+        d.draw_text("WASD mover  +/- zoom", x, hint_y, 14, Color::LIGHTGRAY);
+        d.draw_text("H camino  0 centrar  TAB cerrar", x, hint_y + 16, 14, Color::LIGHTGRAY);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::records::format_time;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectiveKind {
+    ReachExit,
+    CollectGems(u32), // objetos que cuentan como gemas (`counts_for gems`)
+    FindAllRunes,     // objetos que cuentan como runas, contra los que hay en el nivel
+    Survive(f32),      // segundos
+    EscapeBefore(f32), // llegar a la salida antes de este tiempo; si no, se pierde
+}
+
+// Objetivo al que suma un objeto recogido (`counts_for` en assets/sprites.def)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tally {
+    Gems,
+    Runes,
+}
+
+impl Tally {
+    pub fn parse(s: &str) -> Option<Tally> {
+        match s {
+            "gems" => Some(Tally::Gems),
+            "runes" => Some(Tally::Runes),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    Pending,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Objective {
+    pub kind: ObjectiveKind,
+    pub required: bool,
+}
+
+// Lo que hace falta para evaluar los objetivos en un frame
+pub struct ObjectiveContext {
+    pub at_exit: bool,
+    pub gems: u32,  // recogidas
+    pub runes: u32, // recogidas
+    pub runes_total: u32,
+    pub elapsed_time: f32,
+}
+
+impl Objective {
+    // Valores de una línea `objective` del .meta: `exit`, `gems N`, `runes`, `survive S`, `escape S`,
+    // seguidos opcionalmente de `optional`
+    pub fn parse(values: &[&str]) -> Result<Self, String> {
+        let (values, required) = match values.last() {
+            Some(&"optional") => (&values[..values.len() - 1], false),
+            _ => (values, true),
+        };
+        let arg = |name: &str| -> Result<f32, String> {
+            values
+                .get(1)
+                .and_then(|v| v.parse::<f32>().ok())
+                .filter(|v| *v > 0.0)
+                .ok_or_else(|| format!("objective {}: se esperaba un número positivo", name))
+        };
+
+        let kind = match values.first() {
+            Some(&"exit") => ObjectiveKind::ReachExit,
+            Some(&"gems") => {
+                let count = values
+                    .get(1)
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or("objective gems: se esperaba un entero positivo")?;
+                ObjectiveKind::CollectGems(count)
+            }
+            Some(&"runes") => ObjectiveKind::FindAllRunes,
+            Some(&"survive") => ObjectiveKind::Survive(arg("survive")?),
+            Some(&"escape") => ObjectiveKind::EscapeBefore(arg("escape")?),
+            Some(other) => return Err(format!("objetivo desconocido '{}'", other)),
+            None => return Err("objective: falta el tipo".to_string()),
+        };
+        Ok(Objective { kind, required })
+    }
+
+    // Se recalcula cada frame a partir del estado de la partida, así no hay que guardarlo aparte
+    pub fn progress(&self, ctx: &ObjectiveContext) -> Progress {
+        let done = match self.kind {
+            ObjectiveKind::ReachExit => ctx.at_exit,
+            ObjectiveKind::CollectGems(n) => ctx.gems >= n,
+            ObjectiveKind::FindAllRunes => ctx.runes >= ctx.runes_total,
+            ObjectiveKind::Survive(t) => ctx.elapsed_time >= t,
+            ObjectiveKind::EscapeBefore(t) => {
+                if ctx.elapsed_time > t {
+                    return Progress::Failed;
+                }
+                ctx.at_exit
+            }
+        };
+        if done { Progress::Done } else { Progress::Pending }
+    }
+
+    pub fn describe(&self, ctx: &ObjectiveContext) -> String {
+        let text = match self.kind {
+            ObjectiveKind::ReachExit => "Llegar a la salida".to_string(),
+            ObjectiveKind::CollectGems(n) => format!("Gemas {}/{}", ctx.gems.min(n), n),
+            ObjectiveKind::FindAllRunes => format!("Runas {}/{}", ctx.runes, ctx.runes_total),
+            ObjectiveKind::Survive(t) => {
+                format!("Sobrevivir {} / {}", format_time(ctx.elapsed_time.min(t)), format_time(t))
+            }
+            ObjectiveKind::EscapeBefore(t) => {
+                format!("Escapar antes de {} (quedan {})", format_time(t), format_time((t - ctx.elapsed_time).max(0.0)))
+            }
+        };
+        if self.required { text } else { format!("{} (opcional)", text) }
+    }
+}
+
+// Done cuando se cumplieron todos los obligatorios; Failed si alguno obligatorio ya no se puede cumplir
+pub fn evaluate(objectives: &[Objective], ctx: &ObjectiveContext) -> Progress {
+    let mut result = Progress::Done;
+    for objective in objectives.iter().filter(|o| o.required) {
+        match objective.progress(ctx) {
+            Progress::Failed => return Progress::Failed,
+            Progress::Pending => result = Progress::Pending,
+            Progress::Done => {}
+        }
+    }
+    result
+}
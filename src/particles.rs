@@ -0,0 +1,304 @@
+use raylib::prelude::*;
+
+use crate::archetypes::Archetypes;
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::level::Level;
+use crate::player::Player;
+use crate::rng::Rng;
+use crate::spatial::{SpatialIndex, near_visible};
+use crate::textures::TextureManager;
+use crate::WALL_HEIGHT;
+
+const MAX_PARTICLES_PER_EMITTER: usize = 512;
+
+// Parámetros de un emisor. Velocidades en unidades del mundo por segundo; `z` es la altura.
+#[derive(Clone)]
+pub struct EmitterConfig {
+    pub rate: f32,                // partículas por segundo (0 = sólo ráfagas)
+    pub lifetime: (f32, f32),     // segundos, mínimo y máximo
+    pub spread: f32,              // radio alrededor del emisor donde nacen
+    pub height: (f32, f32),       // altura de nacimiento
+    pub speed: (f32, f32),        // velocidad horizontal en una dirección al azar
+    pub rise: (f32, f32),         // velocidad vertical inicial
+    pub gravity: f32,             // aceleración vertical (negativa = cae)
+    pub size: f32,                // tamaño en unidades del mundo
+    pub color_ramp: Vec<Color>,   // colores a lo largo de la vida, repartidos de forma pareja
+    pub texture: Option<usize>,   // hoja de un tipo de sprite; si no, un cuadrado del color de la rampa
+    pub blend: BlendMode,
+}
+
+impl EmitterConfig {
+    // Polvo suspendido que flota lentamente
+    pub fn dust() -> Self {
+        EmitterConfig {
+            rate: 40.0,
+            lifetime: (2.0, 4.0),
+            spread: 220.0,
+            height: (5.0, 95.0),
+            speed: (2.0, 8.0),
+            rise: (-3.0, 3.0),
+            gravity: 0.0,
+            size: 1.2,
+            color_ramp: vec![
+                Color::new(200, 190, 170, 0),
+                Color::new(200, 190, 170, 140),
+                Color::new(200, 190, 170, 0),
+            ],
+            texture: None,
+            blend: BlendMode::Alpha,
+        }
+    }
+
+    // Destellos que suben alrededor del premio
+    pub fn sparkles() -> Self {
+        EmitterConfig {
+            rate: 25.0,
+            lifetime: (0.6, 1.4),
+            spread: 30.0,
+            height: (10.0, 60.0),
+            speed: (0.0, 10.0),
+            rise: (15.0, 35.0),
+            gravity: -5.0,
+            size: 2.0,
+            color_ramp: vec![Color::new(255, 255, 255, 255), Color::GOLD, Color::new(255, 140, 0, 0)],
+            texture: None,
+            blend: BlendMode::Additive,
+        }
+    }
+
+    // Estallido al llegar a la meta, con la textura del tipo `texture` si se da
+    pub fn goal_burst(texture: Option<usize>) -> Self {
+        EmitterConfig {
+            rate: 0.0,
+            lifetime: (1.0, 2.0),
+            spread: 10.0,
+            height: (40.0, 60.0),
+            speed: (60.0, 180.0),
+            rise: (40.0, 140.0),
+            gravity: -160.0,
+            size: 3.0,
+            color_ramp: vec![Color::WHITE, Color::GOLD, Color::new(255, 60, 120, 255), Color::new(80, 0, 120, 0)],
+            texture,
+            blend: BlendMode::Additive,
+        }
+    }
+
+    fn color_at(&self, t: f32) -> Color {
+        match self.color_ramp.len() {
+            0 => Color::WHITE,
+            1 => self.color_ramp[0],
+            n => {
+                let pos = t.clamp(0.0, 1.0) * (n - 1) as f32;
+                let i = (pos as usize).min(n - 2);
+                let f = pos - i as f32;
+                let (a, b) = (self.color_ramp[i], self.color_ramp[i + 1]);
+                let lerp = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * f) as u8;
+                Color::new(lerp(a.r, b.r), lerp(a.g, b.g), lerp(a.b, b.b), lerp(a.a, b.a))
+            }
+        }
+    }
+}
+
+struct Particle {
+    pos: Vector3, // x, y en el plano del piso; z altura
+    vel: Vector3,
+    age: f32,
+    lifetime: f32,
+}
+
+pub struct Emitter {
+    pub config: EmitterConfig,
+    pub pos: Vector2,
+    pub active: bool, // si emite de forma continua; las partículas vivas siguen aunque se apague
+    one_shot: bool,   // ráfaga: se descarta cuando no le quedan partículas
+    accumulator: f32,
+    particles: Vec<Particle>,
+}
+
+impl Emitter {
+    pub fn new(config: EmitterConfig, pos: Vector2) -> Self {
+        Emitter { config, pos, active: true, one_shot: false, accumulator: 0.0, particles: Vec::new() }
+    }
+
+    pub fn burst(&mut self, count: usize, rng: &mut Rng) {
+        for _ in 0..count {
+            self.spawn(rng);
+        }
+    }
+
+    fn spawn(&mut self, rng: &mut Rng) {
+        if self.particles.len() >= MAX_PARTICLES_PER_EMITTER {
+            return;
+        }
+        let c = &self.config;
+        let angle = rng.range(0.0, std::f32::consts::TAU);
+        let radius = c.spread * rng.next_f32().sqrt();
+        let heading = rng.range(0.0, std::f32::consts::TAU);
+        let speed = rng.range(c.speed.0, c.speed.1);
+
+        self.particles.push(Particle {
+            pos: Vector3::new(
+                self.pos.x + angle.cos() * radius,
+                self.pos.y + angle.sin() * radius,
+                rng.range(c.height.0, c.height.1),
+            ),
+            vel: Vector3::new(heading.cos() * speed, heading.sin() * speed, rng.range(c.rise.0, c.rise.1)),
+            age: 0.0,
+            lifetime: rng.range(c.lifetime.0, c.lifetime.1),
+        });
+    }
+
+    fn update(&mut self, delta_time: f32, rng: &mut Rng) {
+        if self.active && self.config.rate > 0.0 {
+            self.accumulator += self.config.rate * delta_time;
+            while self.accumulator >= 1.0 {
+                self.accumulator -= 1.0;
+                self.spawn(rng);
+            }
+        }
+
+        let gravity = self.config.gravity;
+        for p in &mut self.particles {
+            p.age += delta_time;
+            p.vel.z += gravity * delta_time;
+            p.pos.x += p.vel.x * delta_time;
+            p.pos.y += p.vel.y * delta_time;
+            p.pos.z = (p.pos.z + p.vel.z * delta_time).max(0.0); // no atraviesan el piso
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+    }
+}
+
+pub struct ParticleSystem {
+    pub emitters: Vec<Emitter>,
+    dust: usize, // índice del emisor de polvo de la zona especial
+}
+
+impl ParticleSystem {
+    // Efectos del nivel: polvo en la zona especial (sigue al jugador) y el `effect` de cada tipo de sprite
+    pub fn for_level(level: &Level, archetypes: &Archetypes) -> Self {
+        let mut dust = Emitter::new(EmitterConfig::dust(), level.player_start);
+        dust.active = false;
+        let mut emitters = vec![dust];
+
+        for spawn in &level.sprite_spawns {
+            let config = match archetypes.get(spawn.archetype).effect.as_deref() {
+                Some("sparkles") => EmitterConfig::sparkles(),
+                Some(other) => {
+                    log_warn!("Efecto de partículas desconocido '{}'", other);
+                    continue;
+                }
+                None => continue,
+            };
+            emitters.push(Emitter::new(config, spawn.pos));
+        }
+
+        ParticleSystem { emitters, dust: 0 }
+    }
+
+    pub fn update_dust(&mut self, player_pos: Vector2, in_special_zone: bool) {
+        if let Some(emitter) = self.emitters.get_mut(self.dust) {
+            emitter.pos = player_pos;
+            emitter.active = in_special_zone;
+        }
+    }
+
+    // Emisor de una sola ráfaga; se descarta cuando no le quedan partículas
+    pub fn burst(&mut self, config: EmitterConfig, pos: Vector2, count: usize, rng: &mut Rng) {
+        let mut emitter = Emitter::new(config, pos);
+        emitter.active = false;
+        emitter.one_shot = true;
+        emitter.burst(count, rng);
+        self.emitters.push(emitter);
+    }
+
+    pub fn update(&mut self, delta_time: f32, rng: &mut Rng) {
+        for emitter in &mut self.emitters {
+            emitter.update(delta_time, rng);
+        }
+
+        // `retain` conserva el orden, así el índice del polvo sigue siendo válido
+        self.emitters.retain(|e| !(e.one_shot && e.particles.is_empty()));
+    }
+
+    pub fn count(&self) -> usize {
+        self.emitters.iter().map(|e| e.particles.len()).sum()
+    }
+
+    // Partículas delante de la cámara en celdas visibles o junto a ellas (como los sprites de
+    // `SpatialIndex::query_visible`), con su profundidad para ordenarlas junto con los sprites
+    pub fn visible(&self, player: &Player, spatial: &SpatialIndex, visible: impl Fn(i32, i32) -> bool) -> Vec<VisibleParticle> {
+        let (sin_a, cos_a) = player.a.sin_cos();
+        let mut result = Vec::new();
+
+        for (e, emitter) in self.emitters.iter().enumerate() {
+            for (p, particle) in emitter.particles.iter().enumerate() {
+                let dx = particle.pos.x - player.pos.x;
+                let dy = particle.pos.y - player.pos.y;
+                let depth = dx * cos_a + dy * sin_a;
+                if depth < 1.0 {
+                    continue;
+                }
+                let (cx, cy) = spatial.cell_of(Vector2::new(particle.pos.x, particle.pos.y));
+                if near_visible(cx as i32, cy as i32, &visible) {
+                    result.push(VisibleParticle { depth, emitter: e, particle: p });
+                }
+            }
+        }
+        result
+    }
+
+    // Cada partícula es un billboard diminuto proyectado igual que los sprites y con prueba de profundidad
+    pub fn draw(&self, framebuffer: &mut Framebuffer, player: &Player, item: &VisibleParticle, texture_cache: &TextureManager) {
+        let emitter = &self.emitters[item.emitter];
+        let p = &emitter.particles[item.particle];
+        let config = &emitter.config;
+        let sheet = config.texture.and_then(|t| texture_cache.get_sprite_sheet(t).map(|s| (t, s)));
+
+        let hh = framebuffer.height as f32 / 2.0;
+        let screen_width = framebuffer.width as f32;
+        let (sin_a, cos_a) = player.a.sin_cos();
+        let depth = item.depth;
+        let side = -(p.pos.x - player.pos.x) * sin_a + (p.pos.y - player.pos.y) * cos_a;
+
+        // Como los sprites: `hh / depth` en vertical y `screen_width / (fov * depth)` en horizontal
+        let scale = hh / depth;
+        let scale_x = screen_width / (player.fov * depth);
+        let screen_x = (side.atan2(depth) / player.fov + 0.5) * screen_width;
+        let screen_y = hh - (p.pos.z - WALL_HEIGHT / 2.0) * scale;
+        let width = (config.size * scale_x).max(1.0);
+        let height = (config.size * scale).max(1.0);
+
+        let color = config.color_at(p.age / p.lifetime);
+        let left = screen_x - width / 2.0;
+        let top = screen_y - height / 2.0;
+
+        for py in top.max(0.0) as i32..(top + height).min(framebuffer.height as f32) as i32 {
+            for px in left.max(0.0) as i32..(left + width).min(screen_width) as i32 {
+                let pixel = match sheet {
+                    Some((texture, sheet)) => {
+                        let rect = sheet.frame_rect(0, 0);
+                        let tx = rect.x as u32 + ((px as f32 - left) / width * rect.width) as u32;
+                        let ty = rect.y as u32 + ((py as f32 - top) / height * rect.height) as u32;
+                        let texel = texture_cache.get_sprite_pixel_color(texture, tx, ty);
+                        tint(texel, color)
+                    }
+                    None => color,
+                };
+                framebuffer.blend_pixel_depth(px as u32, py as u32, pixel, config.blend, 1.0, depth);
+            }
+        }
+    }
+}
+
+// Referencia a una partícula visible este frame
+pub struct VisibleParticle {
+    pub depth: f32, // en el espacio de la cámara, como la de los sprites
+    emitter: usize,
+    particle: usize,
+}
+
+fn tint(texel: Color, color: Color) -> Color {
+    let mul = |a: u8, b: u8| (a as u16 * b as u16 / 255) as u8;
+    Color::new(mul(texel.r, color.r), mul(texel.g, color.g), mul(texel.b, color.b), mul(texel.a, color.a))
+}
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+
+use crate::maze::Maze;
+
+pub type Cell = (usize, usize); // (columna, fila)
+
+// Una celda es transitable si existe dentro de su fila y está vacía
+pub fn is_walkable(maze: &Maze, cell: Cell) -> bool {
+    let (x, y) = cell;
+    y < maze.len() && maze[y].get(x) == Some(&' ')
+}
+
+// BFS sobre la cuadrícula (4 vecinos). Devuelve las celdas desde `start` hasta `goal`, ambas incluidas.
+pub fn shortest_path(maze: &Maze, start: Cell, goal: Cell) -> Option<Vec<Cell>> {
+    if !is_walkable(maze, start) || !is_walkable(maze, goal) {
+        return None;
+    }
+
+    let width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+    let height = maze.len();
+    let index = |(x, y): Cell| y * width + x;
+
+    let mut came_from: Vec<Option<Cell>> = vec![None; width * height];
+    let mut visited = vec![false; width * height];
+    let mut queue = VecDeque::new();
+
+    visited[index(start)] = true;
+    queue.push_back(start);
+
+    while let Some(current) = queue.pop_front() {
+        if current == goal {
+            let mut path = vec![goal];
+            let mut cell = goal;
+            while let Some(prev) = came_from[index(cell)] {
+                path.push(prev);
+                cell = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let (x, y) = current;
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+
+        for next in neighbors {
+            if next.0 >= width || next.1 >= height || !is_walkable(maze, next) {
+                continue;
+            }
+            if !visited[index(next)] {
+                visited[index(next)] = true;
+                came_from[index(next)] = Some(current);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn finds_the_shortest_route_around_walls() {
+        let maze = maze(&["+-----+", "|     |", "| +-+ |", "|     |", "+-----+"]);
+        let path = shortest_path(&maze, (1, 1), (1, 3)).unwrap();
+        assert_eq!(path, vec![(1, 1), (1, 2), (1, 3)]);
+
+        let path = shortest_path(&maze, (2, 1), (4, 3)).unwrap();
+        assert_eq!(path.first(), Some(&(2, 1)));
+        assert_eq!(path.last(), Some(&(4, 3)));
+        assert_eq!(path.len(), 7);
+        for pair in path.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(a.0.abs_diff(b.0) + a.1.abs_diff(b.1), 1);
+            assert!(is_walkable(&maze, b));
+        }
+    }
+
+    #[test]
+    fn start_equal_to_goal() {
+        let maze = maze(&["+-+", "| |", "+-+"]);
+        assert_eq!(shortest_path(&maze, (1, 1), (1, 1)), Some(vec![(1, 1)]));
+    }
+
+    #[test]
+    fn no_path_through_walls_or_out_of_the_maze() {
+        let maze = maze(&["+--+--+", "|  |  |", "+--+--+"]);
+        assert_eq!(shortest_path(&maze, (1, 1), (4, 1)), None);
+        assert_eq!(shortest_path(&maze, (1, 1), (0, 0)), None);
+        assert_eq!(shortest_path(&maze, (1, 1), (10, 10)), None);
+    }
+
+    #[test]
+    fn ragged_rows_are_handled() {
+        let maze = maze(&["+---+", "|   ", "+-"]);
+        assert!(is_walkable(&maze, (3, 1)));
+        assert!(!is_walkable(&maze, (3, 2)));
+        assert_eq!(shortest_path(&maze, (1, 1), (3, 1)).map(|p| p.len()), Some(3));
+    }
+}
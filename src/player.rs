@@ -9,9 +9,13 @@ pub struct Player {
     pub fov: f32,
 }
 
-pub fn process_events(window: &RaylibHandle, player: &mut Player, block_size: usize, maze: &Maze) {
-    const MOVE_SPEED: f32 = 10.0;
-    const ROTATION_SPEED: f32 = PI / 20.0;
+// Desplazamiento por frame (el juego corre a 60 FPS)
+pub const MOVE_SPEED: f32 = 10.0;
+const ROTATION_SPEED: f32 = PI / 20.0;
+pub const PLAYER_RADIUS: f32 = 16.0; // para el contacto con criaturas
+
+// Devuelve la celda con la que chocó el jugador, si intentó moverse contra una
+pub fn process_events(window: &RaylibHandle, player: &mut Player, block_size: usize, maze: &Maze) -> Option<(usize, usize)> {
 
     if window.is_key_down(KeyboardKey::KEY_LEFT) {
         player.a -= ROTATION_SPEED; 
@@ -44,8 +48,11 @@ pub fn process_events(window: &RaylibHandle, player: &mut Player, block_size: us
         if grid_y < maze.len() && grid_x < maze[grid_y].len() && maze[grid_y][grid_x] == ' ' {
             // Si el camino está libre, actualizamos la posición del jugador.
             player.pos = next_pos;
+        } else if grid_y < maze.len() && grid_x < maze[grid_y].len() {
+            // Es una pared (o una puerta): el jugador no se mueve
+            return Some((grid_x, grid_y));
         }
-        // Si la condición no se cumple (es una pared), no hacemos nada.
-        // El jugador simplemente no se moverá, quedando bloqueado por la pared.
     }
+
+    None
 }
\ No newline at end of file
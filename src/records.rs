@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::maze::Maze;
+
+pub const RECORDS_FILE: &str = "records.txt";
+
+// Mejores tiempos por nivel, identificados por el hash del contenido del laberinto
+pub struct Records {
+    pub path: String,
+    pub best_times: HashMap<u64, f32>,
+}
+
+impl Records {
+    // Formato: una línea por nivel con `<hash hex> <segundos>`
+    pub fn load(path: &str) -> Self {
+        let mut best_times = HashMap::new();
+
+        if let Ok(content) = fs::read_to_string(path) {
+            for line in content.lines() {
+                let mut parts = line.split_whitespace();
+                let (Some(hash), Some(time)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+                if let (Ok(hash), Ok(time)) = (u64::from_str_radix(hash, 16), time.parse::<f32>()) {
+                    best_times.insert(hash, time);
+                }
+            }
+        }
+
+        Records { path: path.to_string(), best_times }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let mut entries: Vec<_> = self.best_times.iter().collect();
+        entries.sort_by_key(|(hash, _)| **hash);
+
+        let content: String = entries
+            .iter()
+            .map(|(hash, time)| format!("{:016x} {:.3}\n", hash, time))
+            .collect();
+
+        fs::write(&self.path, content)
+    }
+
+    pub fn best_time(&self, level_hash: u64) -> Option<f32> {
+        self.best_times.get(&level_hash).copied()
+    }
+
+    // Registra un tiempo; devuelve true si es un nuevo récord
+    pub fn submit(&mut self, level_hash: u64, time: f32) -> bool {
+        match self.best_times.get(&level_hash) {
+            Some(&best) if best <= time => false,
+            _ => {
+                self.best_times.insert(level_hash, time);
+                true
+            }
+        }
+    }
+}
+
+// FNV-1a sobre los glifos del laberinto: estable entre ejecuciones y versiones de Rust
+pub fn maze_hash(maze: &Maze) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |byte: u8| {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    };
+
+    for row in maze {
+        for &cell in row {
+            let mut buf = [0u8; 4];
+            for &byte in cell.encode_utf8(&mut buf).as_bytes() {
+                feed(byte);
+            }
+        }
+        feed(b'\n');
+    }
+
+    hash
+}
+
+// mm:ss.cc
+pub fn format_time(seconds: f32) -> String {
+    let total_cs = (seconds.max(0.0) * 100.0).round() as u32;
+    format!("{:02}:{:02}.{:02}", total_cs / 6000, (total_cs / 100) % 60, total_cs % 100)
+}
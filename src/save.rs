@@ -0,0 +1,359 @@
+use raylib::prelude::*;
+use std::fs;
+
+use crate::animation::Animator;
+use crate::level::Level;
+use crate::pathfinding::Cell;
+use crate::player::Player;
+use crate::inventory::Inventory;
+use crate::sprites::Sprite;
+use crate::{GameState, Mode};
+
+pub const SAVE_VERSION: u32 = 8;
+// Las versiones anteriores guardaban el inventario por posición y nunca se publicaron
+const MIN_SAVE_VERSION: u32 = 7;
+pub const SAVE_FILE: &str = "partida.sav";
+pub const QUICKSAVE_FILE: &str = "quicksave.sav";
+
+const SAVE_HEADER: &str = "raycaster-save";
+
+pub struct SpriteSave {
+    pub pos: Vector2,
+    pub is_alive: bool,
+    pub current_frame: usize,
+    pub frame_time: f32,
+    pub clip: Option<(String, usize)>, // clip del animador y frame dentro de él, desde la versión 8
+}
+
+// Foto del estado de la partida. Se guarda como texto: una entrada por línea, `clave valores...`
+pub struct SaveData {
+    pub version: u32,
+    pub level_path: String,
+    pub player_pos: Vector2,
+    pub player_angle: f32,
+    pub elapsed_time: f32,
+    pub in_special_zone: bool,
+    pub sprites: Vec<SpriteSave>,
+    pub explored: Vec<String>,
+    pub health: Option<f32>,
+    pub inventory: Inventory,
+    pub opened_doors: Vec<Cell>,
+}
+
+impl SaveData {
+    pub fn capture(level: &Level, player: &Player, sprites: &[Sprite], game_state: &GameState) -> Self {
+        SaveData {
+            version: SAVE_VERSION,
+            level_path: level.path.clone(),
+            player_pos: player.pos,
+            player_angle: player.a,
+            elapsed_time: game_state.elapsed_time,
+            in_special_zone: game_state.in_special_zone,
+            sprites: sprites
+                .iter()
+                .map(|s| SpriteSave {
+                    pos: s.pos,
+                    is_alive: s.is_alive && !s.collected, // un objeto a medio recoger ya está en el inventario
+                    current_frame: s.current_frame,
+                    frame_time: s.animator.time,
+                    clip: Some((s.animator.clip.clone(), s.animator.frame)),
+                })
+                .collect(),
+            explored: game_state.fog.to_rows(),
+            health: Some(game_state.health),
+            inventory: game_state.inventory.clone(),
+            opened_doors: level.opened_doors(),
+        }
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        let mut out = format!("{} {}\n", SAVE_HEADER, self.version);
+        out += &format!("level {}\n", self.level_path);
+        out += &format!("player {} {} {}\n", self.player_pos.x, self.player_pos.y, self.player_angle);
+        out += &format!("time {}\n", self.elapsed_time);
+        out += &format!("zone {}\n", self.in_special_zone as u8);
+        if let Some(health) = self.health {
+            out += &format!("health {}\n", health);
+        }
+        // Ordenados para que el archivo no cambie entre guardados iguales
+        let mut items: Vec<_> = self.inventory.counts.iter().filter(|(_, n)| **n > 0).collect();
+        items.sort();
+        for (item, count) in items {
+            out += &format!("item {} {}\n", item, count);
+        }
+        for (x, y) in &self.opened_doors {
+            out += &format!("door {} {}\n", x, y);
+        }
+        for s in &self.sprites {
+            out += &format!("sprite {} {} {} {} {}", s.pos.x, s.pos.y, s.is_alive as u8, s.current_frame, s.frame_time);
+            if let Some((clip, frame)) = &s.clip {
+                out += &format!(" {} {}", clip, frame);
+            }
+            out += "\n";
+        }
+        for row in &self.explored {
+            out += &format!("explored {}\n", row);
+        }
+
+        fs::write(path, out).map_err(|e| format!("no se pudo escribir {}: {}", path, e))
+    }
+
+    pub fn read(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("no se pudo leer {}: {}", path, e))?;
+        let mut lines = content.lines().enumerate();
+
+        let version = match lines.next().map(|(_, l)| l.split_whitespace().collect::<Vec<_>>()) {
+            Some(header) if header.len() == 2 && header[0] == SAVE_HEADER => header[1]
+                .parse::<u32>()
+                .map_err(|_| format!("{}: versión inválida '{}'", path, header[1]))?,
+            _ => return Err(format!("{}: no es un archivo de partida", path)),
+        };
+        if !(MIN_SAVE_VERSION..=SAVE_VERSION).contains(&version) {
+            return Err(format!(
+                "{}: versión {} no soportada (de {} a {})",
+                path, version, MIN_SAVE_VERSION, SAVE_VERSION
+            ));
+        }
+
+        let mut data = SaveData {
+            version,
+            level_path: String::new(),
+            player_pos: Vector2::zero(),
+            player_angle: 0.0,
+            elapsed_time: 0.0,
+            in_special_zone: false,
+            sprites: Vec::new(),
+            explored: Vec::new(),
+            health: None,
+            inventory: Inventory::default(),
+            opened_doors: Vec::new(),
+        };
+
+        for (i, line) in lines {
+            let line_no = i + 1;
+            let mut parts = line.split_whitespace();
+            let Some(key) = parts.next() else { continue };
+            let values: Vec<&str> = parts.collect();
+            let num = |idx: usize| -> Result<f32, String> {
+                values
+                    .get(idx)
+                    .and_then(|v| v.parse::<f32>().ok())
+                    .ok_or_else(|| format!("{}:{}: valor inválido en '{}'", path, line_no, key))
+            };
+
+            match key {
+                "level" => data.level_path = values.join(" "),
+                "player" => {
+                    data.player_pos = Vector2::new(num(0)?, num(1)?);
+                    data.player_angle = num(2)?;
+                }
+                "time" => data.elapsed_time = num(0)?,
+                "zone" => data.in_special_zone = num(0)? != 0.0,
+                "health" => data.health = Some(num(0)?),
+                "item" => {
+                    let name = values.first().ok_or_else(|| format!("{}:{}: falta el objeto", path, line_no))?;
+                    data.inventory.counts.insert(name.to_string(), num(1)? as u32);
+                }
+                "door" => data.opened_doors.push((num(0)? as usize, num(1)? as usize)),
+                "sprite" => data.sprites.push(SpriteSave {
+                    pos: Vector2::new(num(0)?, num(1)?),
+                    is_alive: num(2)? != 0.0,
+                    current_frame: num(3)? as usize,
+                    frame_time: num(4)?,
+                    clip: match values.get(5) {
+                        Some(clip) => Some((clip.to_string(), num(6)? as usize)),
+                        None => None,
+                    },
+                }),
+                "explored" => data.explored.push(values.concat()),
+                // Claves de versiones más nuevas del mismo formato se ignoran
+                _ => {}
+            }
+        }
+
+        if data.level_path.is_empty() {
+            return Err(format!("{}: falta el nivel", path));
+        }
+
+        Ok(data)
+    }
+
+    // Restaura la partida sobre un nivel recién cargado (el de `level_path`)
+    pub fn apply(
+        &self,
+        level: &mut Level,
+        player: &mut Player,
+        sprites: &mut Vec<Sprite>,
+        game_state: &mut GameState,
+    ) -> Result<(), String> {
+        let mut restored = level.spawn_sprites();
+        if restored.len() != self.sprites.len() {
+            return Err(format!(
+                "la partida tiene {} sprites y el nivel {}",
+                self.sprites.len(),
+                restored.len()
+            ));
+        }
+
+        for (sprite, saved) in restored.iter_mut().zip(&self.sprites) {
+            sprite.pos = saved.pos;
+            sprite.is_alive = saved.is_alive;
+            sprite.current_frame = saved.current_frame % sprite.frame_count.max(1);
+            // La versión 7 sólo guardaba el tiempo: el clip es el inicial
+            if let Some((clip, frame)) = &saved.clip {
+                sprite.animator = Animator::new(clip);
+                sprite.animator.frame = *frame;
+            }
+            sprite.animator.time = saved.frame_time;
+        }
+
+        let mut state = GameState::new(level);
+        state.elapsed_time = self.elapsed_time;
+        state.in_special_zone = self.in_special_zone;
+        state.flashlight_active = self.in_special_zone;
+        state.mode = Mode::Playing;
+        if let Some(health) = self.health {
+            state.health = health;
+        }
+        state.inventory = self.inventory.clone();
+
+        // Sin filas de niebla guardadas la niebla empieza de cero
+        if !self.explored.is_empty() {
+            state.fog.load_rows(&self.explored)?;
+        }
+
+        level.close_doors();
+        for &cell in &self.opened_doors {
+            if level.door_color(cell).is_none() {
+                log_warn!("la partida abre una puerta inexistente en {:?}", cell);
+            }
+            level.open_door(cell);
+        }
+
+        *sprites = restored;
+        *player = Player { pos: self.player_pos, a: self.player_angle, fov: player.fov };
+        *game_state = state;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetypes::Archetypes;
+    use crate::testutil::{TempFile, SMALL_MAZE};
+
+    const META: &str = "\
+sprite creature 200 100
+sprite gem 120 200
+sprite rune 400 240
+";
+
+    fn level(name: &str, archetypes: &Archetypes) -> Level {
+        let _meta = TempFile::new(&format!("{}.meta", name), META);
+        let file = TempFile::new(&format!("{}.txt", name), SMALL_MAZE);
+        Level::load(file.path(), archetypes, 80).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let archetypes = Archetypes::builtin();
+        let mut level = level("save_round_trip", &archetypes);
+        let player = Player { pos: Vector2::new(130.0, 150.0), a: 1.25, fov: 1.0 };
+        let mut sprites = level.spawn_sprites();
+        let mut state = GameState::new(&level);
+
+        sprites[0].pos = Vector2::new(210.5, 110.25);
+        sprites[0].animator.play("walk");
+        sprites[0].animator.frame = 2;
+        sprites[0].animator.time = 0.05;
+        sprites[0].current_frame = 2;
+        sprites[1].collected = true; // a medio recoger: ya está en el inventario
+        state.elapsed_time = 42.5;
+        state.health = 37.0;
+        state.in_special_zone = true;
+        state.inventory.add("gem");
+        state.inventory.add("key_red");
+        state.fog.reveal(1, 1);
+        let door = level.doors[0].cell;
+        level.open_door(door);
+
+        let file = TempFile::new("round_trip.sav", "");
+        SaveData::capture(&level, &player, &sprites, &state).write(file.path()).unwrap();
+        let data = SaveData::read(file.path()).unwrap();
+        assert_eq!(data.version, SAVE_VERSION);
+
+        let mut restored_level = self::level("save_round_trip", &archetypes);
+        let mut restored_player = Player { pos: Vector2::zero(), a: 0.0, fov: 1.0 };
+        let mut restored_sprites = Vec::new();
+        let mut restored_state = GameState::new(&restored_level);
+        data.apply(&mut restored_level, &mut restored_player, &mut restored_sprites, &mut restored_state)
+            .unwrap();
+
+        assert_eq!((restored_player.pos, restored_player.a), (player.pos, player.a));
+        assert_eq!(restored_state.elapsed_time, 42.5);
+        assert_eq!(restored_state.health, 37.0);
+        assert!(restored_state.in_special_zone && restored_state.flashlight_active);
+        assert_eq!(restored_state.inventory.count("gem"), 1);
+        assert_eq!(restored_state.inventory.count("key_red"), 1);
+        assert!(restored_state.fog.is_revealed(1, 1));
+        assert_eq!(restored_level.opened_doors(), vec![door]);
+
+        let creature = &restored_sprites[0];
+        assert_eq!(creature.pos, Vector2::new(210.5, 110.25));
+        assert_eq!((creature.animator.clip.as_str(), creature.animator.frame), ("walk", 2));
+        assert_eq!((creature.animator.time, creature.current_frame), (0.05, 2));
+        assert!(!restored_sprites[1].is_alive);
+        assert!(restored_sprites[2].is_alive);
+    }
+
+    #[test]
+    fn reads_version_7_without_clips() {
+        let archetypes = Archetypes::builtin();
+        let mut level = level("save_v7", &archetypes);
+        let content = format!(
+            "raycaster-save 7\nlevel {}\nplayer 130 150 0.5\ntime 12\nzone 0\nhealth 80\nitem gem 2\nitem key_green 1\n\
+             sprite 200 100 1 3 0.1\nsprite 120 200 0 0 0\nsprite 400 240 1 0 0\n",
+            level.path
+        );
+        let data = SaveData::read(TempFile::new("v7.sav", &content).path()).unwrap();
+        assert_eq!(data.sprites[0].clip, None);
+
+        let mut player = Player { pos: Vector2::zero(), a: 0.0, fov: 1.0 };
+        let mut sprites = Vec::new();
+        let mut state = GameState::new(&level);
+        data.apply(&mut level, &mut player, &mut sprites, &mut state).unwrap();
+
+        assert_eq!((state.inventory.count("gem"), state.inventory.count("key_green")), (2, 1));
+        assert_eq!(state.health, 80.0);
+        // Sin clip guardado se conserva el inicial
+        assert_eq!((sprites[0].animator.clip.as_str(), sprites[0].current_frame), ("idle", 3));
+        assert!(!sprites[1].is_alive);
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        let err = |name: &str, content: &str| SaveData::read(TempFile::new(name, content).path()).err().unwrap();
+        assert!(err("bad_header.sav", "partida 1\n").contains("no es un archivo de partida"));
+        assert!(err("bad_version.sav", "raycaster-save uno\n").contains("versión inválida"));
+        assert!(err("future.sav", &format!("raycaster-save {}\n", SAVE_VERSION + 1)).contains("no soportada"));
+        assert!(err("v1.sav", "raycaster-save 1\nlevel x\n").contains("versión 1 no soportada"));
+        assert!(err("v6.sav", "raycaster-save 6\nlevel x\ninventory 1 0 1\n").contains("versión 6 no soportada"));
+        assert!(err("no_level.sav", "raycaster-save 8\nplayer 1 2 3\n").contains("falta el nivel"));
+        assert!(err("bad_value.sav", "raycaster-save 8\nlevel x\nplayer 1 dos 3\n").contains(":3:"));
+        assert!(SaveData::read("no-existe.sav").is_err());
+    }
+
+    #[test]
+    fn sprite_count_must_match_the_level() {
+        let archetypes = Archetypes::builtin();
+        let mut level = level("save_mismatch", &archetypes);
+        let content = format!("raycaster-save 8\nlevel {}\nsprite 1 1 1 0 0\n", level.path);
+        let data = SaveData::read(TempFile::new("mismatch.sav", &content).path()).unwrap();
+        let (mut player, mut sprites) = (Player { pos: Vector2::zero(), a: 0.0, fov: 1.0 }, Vec::new());
+        let mut state = GameState::new(&level);
+        let err = data.apply(&mut level, &mut player, &mut sprites, &mut state).unwrap_err();
+        assert!(err.contains("1 sprites y el nivel 3"), "{}", err);
+    }
+}
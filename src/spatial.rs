@@ -0,0 +1,84 @@
+use raylib::prelude::*;
+
+use crate::maze::Maze;
+use crate::sprites::Sprite;
+
+// Índices de sprites agrupados por celda del laberinto. Se reconstruye cada frame (O(n)) y permite
+// consultar sólo las celdas cercanas en vez de recorrer todos los sprites.
+pub struct SpatialIndex {
+    block_size: f32,
+    width: usize,
+    height: usize,
+    buckets: Vec<Vec<usize>>,
+}
+
+impl SpatialIndex {
+    pub fn new(block_size: usize) -> Self {
+        SpatialIndex { block_size: block_size as f32, width: 0, height: 0, buckets: Vec::new() }
+    }
+
+    // Sólo entran los sprites vivos; lo que cae fuera del laberinto va a la celda del borde más cercana
+    pub fn rebuild(&mut self, maze: &Maze, sprites: &[Sprite]) {
+        let width = maze.iter().map(|row| row.len()).max().unwrap_or(0).max(1);
+        let height = maze.len().max(1);
+        if width != self.width || height != self.height {
+            self.width = width;
+            self.height = height;
+            self.buckets = vec![Vec::new(); width * height];
+        } else {
+            self.buckets.iter_mut().for_each(Vec::clear);
+        }
+
+        for (i, sprite) in sprites.iter().enumerate().filter(|(_, s)| s.is_alive) {
+            let (x, y) = self.cell_of(sprite.pos);
+            self.buckets[y * self.width + x].push(i);
+        }
+    }
+
+    pub fn cell_of(&self, pos: Vector2) -> (usize, usize) {
+        let x = (pos.x / self.block_size).floor().clamp(0.0, (self.width - 1) as f32) as usize;
+        let y = (pos.y / self.block_size).floor().clamp(0.0, (self.height - 1) as f32) as usize;
+        (x, y)
+    }
+
+    pub fn bucket(&self, x: usize, y: usize) -> &[usize] {
+        if x < self.width && y < self.height { &self.buckets[y * self.width + x] } else { &[] }
+    }
+
+    // Candidatos en las celdas que toca el círculo; quien llama comprueba la distancia exacta
+    pub fn query_radius(&self, center: Vector2, radius: f32) -> Vec<usize> {
+        let (min_x, min_y) = self.cell_of(Vector2::new(center.x - radius, center.y - radius));
+        let (max_x, max_y) = self.cell_of(Vector2::new(center.x + radius, center.y + radius));
+
+        let mut result = Vec::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                result.extend_from_slice(self.bucket(x, y));
+            }
+        }
+        result
+    }
+
+    // Sprites en celdas para las que `visible(x, y)` es true o que tienen una vecina visible
+    // (un sprite puede asomar desde la celda de al lado)
+    pub fn query_visible(&self, visible: impl Fn(i32, i32) -> bool) -> Vec<usize> {
+        let mut result = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let bucket = &self.buckets[y * self.width + x];
+                if bucket.is_empty() {
+                    continue;
+                }
+                if near_visible(x as i32, y as i32, &visible) {
+                    result.extend_from_slice(bucket);
+                }
+            }
+        }
+        result
+    }
+}
+
+// true si la celda o alguna de sus vecinas es visible
+pub fn near_visible(cx: i32, cy: i32, visible: &impl Fn(i32, i32) -> bool) -> bool {
+    (-1..=1).any(|dy| (-1..=1).any(|dx| visible(cx + dx, cy + dy)))
+}
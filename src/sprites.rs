@@ -1,59 +1,121 @@
 use raylib::prelude::*;
 use crate::textures::TextureManager;
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum SpriteType {
-    creature,  
-    prize,
-}
+use crate::ai::Brain;
+use crate::animation::{AnimEvent, Animator, ClipSet};
+use crate::archetypes::{Archetype, Behavior};
+use crate::framebuffer::BlendMode;
+
+#[derive(Clone)]
 pub struct Sprite {
     pub pos: Vector2,
-    pub texture_key: char,
+    pub archetype: usize, // índice en el registro de tipos (assets/sprites.def)
     pub current_frame: usize,
     pub frame_count: usize,
-    pub frame_delay: f32,
-    pub frame_time: f32,
+    pub animator: Animator,
     pub is_alive: bool,
-    pub sprite_type: SpriteType,
-    pub sound_key: &'static str
+    pub collected: bool, // recogido, mostrando el clip `pickup` antes de desaparecer
+    pub item: Option<String>, // objeto del inventario que da al recogerlo
+    pub sound_key: String,    // sonido de proximidad; vacío si no tiene
+    pub radius: f32,
+    pub size: f32,            // alto en unidades del mundo (una pared mide 100)
+    pub z_offset: f32,        // altura de la base sobre el piso
+    pub blend: BlendMode,
+    pub opacity: f32,         // 0..1, multiplica el alfa de la textura
+    pub facing: f32,          // ángulo hacia donde mira
+    pub brain: Option<Brain>, // comportamiento de las criaturas
 }
 
 impl Sprite {
-    pub fn new(x: f32, y: f32, texture_key: char, frame_count: usize, sprite_type: SpriteType) -> Self {
-        //Sonidos para cada sprite
-        let sound_key = match sprite_type {
-            SpriteType::creature => "creature_whisper",
-            SpriteType::prize => "shimmering",
-        };
+    pub fn new(x: f32, y: f32, archetype: usize, kind: &Archetype) -> Self {
+        let pos = Vector2::new(x, y);
 
         Sprite {
-            pos: Vector2::new(x, y),
-            texture_key,
+            pos,
+            archetype,
             current_frame: 0,
-            frame_count,
-            frame_delay: 0.2, // Segundos entre frames (más lento para mejor visualización)
-            frame_time: 0.0,
+            frame_count: kind.frames,
+            animator: Animator::new("idle"),
             is_alive: true,
-            sprite_type,
-            sound_key
+            collected: false,
+            item: kind.item().map(str::to_string),
+            sound_key: kind.sound.clone().unwrap_or_default(),
+            radius: kind.radius,
+            size: kind.size,
+            z_offset: kind.z_offset,
+            blend: kind.blend,
+            opacity: kind.opacity,
+            facing: 0.0,
+            brain: (kind.behavior == Behavior::Creature).then(|| Brain::new(pos, Vec::new())),
         }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
+    // Avanza el clip actual con los clips de su hoja; un objeto desaparece al terminar su clip `pickup`
+    pub fn update(&mut self, delta_time: f32, clips: &ClipSet) {
         if !self.is_alive {
             return;
         }
 
-        self.frame_time += delta_time;
-        if self.frame_time >= self.frame_delay {
-            self.frame_time = 0.0;
-            self.current_frame = (self.current_frame + 1) % self.frame_count;
+        let event = self.animator.update(delta_time, clips);
+        if let Some(frame) = self.animator.sheet_frame(clips) {
+            self.current_frame = frame;
+        }
+
+        if let Some(AnimEvent::Finished(clip)) = event
+            && clip == "pickup"
+        {
+            self.is_alive = false;
         }
     }
 
-    // Método para obtener las coordenadas de textura del frame actual
-    pub fn get_frame_coords(&self, texture_width: u32) -> (u32, u32, u32, u32) {
-        let frame_width = texture_width / self.frame_count as u32;
-        let src_x = self.current_frame as u32 * frame_width;
-        (src_x, 0, frame_width, 128) // Asumiendo altura de 128 píxeles
+    // Fila de la hoja según desde dónde lo mira `viewer`: 0 de frente, luego cada 360/directions grados
+    pub fn view_direction(&self, viewer: Vector2, directions: usize) -> usize {
+        if directions <= 1 {
+            return 0;
+        }
+        let to_viewer = (viewer.y - self.pos.y).atan2(viewer.x - self.pos.x);
+        let step = 2.0 * std::f32::consts::PI / directions as f32;
+        let relative = (to_viewer - self.facing).rem_euclid(2.0 * std::f32::consts::PI);
+        (relative / step).round() as usize % directions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetypes::Archetypes;
+    use std::f32::consts::PI;
+
+    fn creature_at(x: f32, y: f32) -> Sprite {
+        let archetypes = Archetypes::builtin();
+        let id = archetypes.find("creature").unwrap();
+        Sprite::new(x, y, id, archetypes.get(id))
+    }
+
+    #[test]
+    fn view_direction_follows_the_viewer_around() {
+        let sprite = creature_at(100.0, 100.0);
+        // Mirando hacia +x: de frente, y luego cada cuarto de vuelta
+        assert_eq!(sprite.view_direction(Vector2::new(200.0, 100.0), 4), 0);
+        assert_eq!(sprite.view_direction(Vector2::new(100.0, 200.0), 4), 1);
+        assert_eq!(sprite.view_direction(Vector2::new(0.0, 100.0), 4), 2);
+        assert_eq!(sprite.view_direction(Vector2::new(100.0, 0.0), 4), 3);
+        // Se redondea a la vista más cercana
+        assert_eq!(sprite.view_direction(Vector2::new(200.0, 130.0), 4), 0);
+        assert_eq!(sprite.view_direction(Vector2::new(200.0, 180.0), 8), 1);
+    }
+
+    #[test]
+    fn view_direction_is_relative_to_facing() {
+        let mut sprite = creature_at(100.0, 100.0);
+        sprite.facing = PI / 2.0;
+        assert_eq!(sprite.view_direction(Vector2::new(100.0, 200.0), 4), 0);
+        assert_eq!(sprite.view_direction(Vector2::new(200.0, 100.0), 4), 3);
+    }
+
+    #[test]
+    fn single_direction_sheets_always_use_row_zero() {
+        let sprite = creature_at(100.0, 100.0);
+        assert_eq!(sprite.view_direction(Vector2::new(0.0, 100.0), 1), 0);
+        assert_eq!(sprite.view_direction(Vector2::new(0.0, 100.0), 0), 0);
     }
-}
\ No newline at end of file
+}
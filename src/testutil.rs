@@ -0,0 +1,35 @@
+use std::fs;
+use std::path::PathBuf;
+
+// Archivo temporal de un test; se borra al salir de alcance
+pub struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    // El nombre lleva el id del proceso para no pisar archivos de otra corrida
+    pub fn new(name: &str, content: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("raycaster-{}-{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        TempFile { path }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Laberinto chico para los tests: pasillo en L con una puerta roja
+pub const SMALL_MAZE: &str = "\
++--+--+
+|     |
++  +R +
+|  |  |
++--+--+
+";
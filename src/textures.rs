@@ -2,64 +2,194 @@ use raylib::prelude::*;
 use std::collections::HashMap;
 use std::slice;
 
-use crate::sprites::SpriteType;
+use crate::animation::ClipSet;
+use crate::archetypes::Archetypes;
+use crate::assets::{AssetReport, Manifest};
+use crate::doors::KeyColor;
+
+const TRANSPARENT: Color = Color::new(0, 0, 0, 0);
+const WALL_TEXTURE_SIZE: i32 = 128;
+const SHEET_CELL_SIZE: i32 = 64;
+
+// Un frame dentro de la hoja: su rectángulo y el punto que se apoya en la posición del sprite
+#[derive(Debug, Clone, Copy)]
+pub struct Frame {
+    pub rect: Rectangle,
+    pub pivot: Vector2, // en píxeles, relativo a la esquina superior izquierda del rectángulo
+}
+
+// Cómo están repartidos los frames en la imagen (se describe en assets/sprites.def).
+// Los frames se numeran seguidos: primero todos los de la vista 0, luego los de la 1...
+// y por defecto ocupan las celdas de una grilla leídas fila por fila.
+#[derive(Debug, Clone, Default)]
+pub struct SheetLayout {
+    pub frame_size: Option<(u32, u32)>, // celda de la grilla; si no, la imagen dividida en frames x vistas
+    pub columns: Option<usize>,         // celdas por fila; si no, las que entran en el ancho
+    pub pivot: Option<Vector2>,         // si no, el centro del borde inferior de cada frame
+    pub frames: Vec<(usize, Rectangle, Option<Vector2>)>, // rectángulos a mano por número de frame
+}
+
+impl SheetLayout {
+    pub fn build(&self, image_width: u32, image_height: u32, frame_count: usize, directions: usize) -> (Vec<Frame>, f32) {
+        let (w, h) = self
+            .frame_size
+            .unwrap_or((image_width / frame_count.max(1) as u32, image_height / directions.max(1) as u32));
+        let columns = self.columns.unwrap_or((image_width / w.max(1)) as usize).max(1);
+
+        let frames = (0..frame_count * directions)
+            .map(|i| {
+                let (rect, pivot) = match self.frames.iter().find(|(n, _, _)| *n == i) {
+                    Some(&(_, rect, pivot)) => (rect, pivot),
+                    None => {
+                        let (col, row) = ((i % columns) as u32, (i / columns) as u32);
+                        (Rectangle::new((col * w) as f32, (row * h) as f32, w as f32, h as f32), None)
+                    }
+                };
+                let pivot = pivot.or(self.pivot).unwrap_or(Vector2::new(rect.width / 2.0, rect.height));
+                Frame { rect, pivot }
+            })
+            .collect();
+
+        (frames, h as f32)
+    }
+}
+
+// Hoja de un sprite: `frame_count` frames de animación para cada una de `directions` vistas.
+// Con 8 direcciones la vista 0 es de frente y cada una de las siguientes gira 45°.
+pub struct SpriteSheet {
+    pub texture: Texture2D,
+    pub image: Image,
+    pub frames: Vec<Frame>,
+    pub frame_count: usize,
+    pub directions: usize,
+    pub reference_height: f32, // alto en píxeles que corresponde al `size` del sprite (el de la celda)
+    pub clips: ClipSet, // de `<imagen>.anim`, junto a la hoja
+}
+
+impl SpriteSheet {
+    pub fn frame(&self, frame: usize, direction: usize) -> Frame {
+        let index = (direction % self.directions.max(1)) * self.frame_count + frame % self.frame_count.max(1);
+        self.frames[index]
+    }
+
+    // Rectángulo del frame dentro de la hoja, para dibujar con raylib
+    pub fn frame_rect(&self, frame: usize, direction: usize) -> Rectangle {
+        self.frame(frame, direction).rect
+    }
+}
 
 pub struct TextureManager {
     images: HashMap<char, Image>,       // Store images for pixel access
     textures: HashMap<char, Texture2D>, // Store GPU textures for rendering
-    pub animations: HashMap<SpriteType, (Texture2D, Image, usize)> // (texture, image, frame_count)
+    pub sheets: Vec<Option<SpriteSheet>>, // por tipo de sprite, en el orden del registro
+    no_clips: ClipSet,
+    decal_images: HashMap<String, Image>, // por ruta, cargadas según los decals del nivel
 }
 
 impl TextureManager {
-    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+    // Lo que no se puede cargar se reemplaza por un tablero de ajedrez y se anota en `report`
+    pub fn new(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        manifest: &Manifest,
+        archetypes: &Archetypes,
+        report: &mut AssetReport,
+    ) -> Self {
         let mut images = HashMap::new();
         let mut textures = HashMap::new();
-        let mut animations = HashMap::new();
-
-        // Map characters to texture file paths
-        let texture_files = vec![
-            ('+', "assets/piedra.png"),
-            ('-', "assets/flores.png"),
-            ('|', "assets/flores.png"),
-            ('l', "assets/1.png"),
-            ('_', "assets/runas.png"),
-            ('#', "assets/hiedra.png"), 
-        ];
-
-        for (ch, path) in texture_files {
-            let image = Image::load_image(path).expect(&format!("Failed to load image {}", path));
-            let texture = rl.load_texture(thread, path).expect(&format!("Failed to load texture {}", path));
+        let mut sheets = Vec::new();
+
+        // Paredes: glifo del laberinto e imagen, del manifiesto
+        let mut walls: Vec<(char, Image)> = manifest
+            .textures
+            .iter()
+            .map(|(ch, path)| {
+                let image = report.check(path, load_image(path));
+                (*ch, image.unwrap_or_else(|| missing_image(WALL_TEXTURE_SIZE, WALL_TEXTURE_SIZE)))
+            })
+            .collect();
+
+        // Puertas: la imagen del manifiesto teñida con el color de su llave
+        let door = match &manifest.door_texture {
+            Some(path) => report.check(path, load_image(path)),
+            None => None,
+        };
+        for color in KeyColor::ALL {
+            let mut image = match &door {
+                Some(image) => image.clone(),
+                None => missing_image(WALL_TEXTURE_SIZE, WALL_TEXTURE_SIZE),
+            };
+            image.color_tint(color.color());
+            walls.push((color.glyph(), image));
+        }
+
+        // La subida a la GPU no es un archivo: si falla se avisa, y el raycaster sigue usando la imagen
+        for (ch, image) in walls {
+            match rl.load_texture_from_image(thread, &image) {
+                Ok(texture) => {
+                    textures.insert(ch, texture);
+                }
+                Err(e) => log_warn!("No se pudo crear la textura '{}': {}", ch, e),
+            }
             images.insert(ch, image);
-            textures.insert(ch, texture);
         }
 
-        let animation_files = vec![
-            (SpriteType::creature, "assets/enemy.png", 4),
-            (SpriteType::prize, "assets/prize.png", 1),
-        ];
+        // Una hoja por tipo de sprite, con su imagen, frames, vistas y tinte de assets/sprites.def
+        for (_, archetype) in archetypes.iter() {
+            let path = archetype.texture.as_str();
+            let (frame_count, directions) = (archetype.frames, archetype.directions);
 
-        for (sprite_type, path, frame_count) in animation_files {
-            if let Ok(image) = Image::load_image(path) {
-                if let Ok(texture) = rl.load_texture_from_image(thread, &image) {
-                    animations.insert(sprite_type, (texture, image, frame_count));
-                }
+            // Sin imagen, un tablero con una celda por frame y la grilla por defecto
+            let (mut image, layout) = match report.check(path, load_image(path)) {
+                Some(image) => (image, archetype.layout.clone()),
+                None => (
+                    missing_image(SHEET_CELL_SIZE * frame_count as i32, SHEET_CELL_SIZE * directions as i32),
+                    SheetLayout::default(),
+                ),
+            };
+            if let Some(tint) = archetype.tint {
+                image.color_tint(tint);
             }
+
+            let anim_path = archetype.clips_path();
+            let clips = if anim_path.is_file() {
+                ClipSet::load(&anim_path.to_string_lossy(), frame_count).unwrap_or_else(|e| {
+                    log_warn!("{}; se usa un único clip", e);
+                    ClipSet::single(frame_count)
+                })
+            } else {
+                ClipSet::single(frame_count)
+            };
+
+            let (frames, reference_height) = layout.build(image.width as u32, image.height as u32, frame_count, directions);
+            let sheet = match rl.load_texture_from_image(thread, &image) {
+                Ok(texture) => Some(SpriteSheet { texture, image, frames, frame_count, directions, reference_height, clips }),
+                Err(e) => {
+                    log_warn!("No se pudo crear la hoja del tipo '{}': {}", archetype.name, e);
+                    None
+                }
+            };
+            sheets.push(sheet);
         }
 
-        TextureManager { images, textures, animations }
+        TextureManager { images, textures, sheets, no_clips: ClipSet::default(), decal_images: HashMap::new() }
     }
 
-    pub fn get_sprite_texture(&self, sprite_type: &SpriteType) -> Option<&Texture2D> {
-        self.animations.get(sprite_type).map(|(texture, _, _)| texture)
+    pub fn get_sprite_texture(&self, archetype: usize) -> Option<&Texture2D> {
+        self.get_sprite_sheet(archetype).map(|sheet| &sheet.texture)
     }
 
-    pub fn get_sprite_animation_info(&self, sprite_type: &SpriteType) -> Option<(u32, usize)> {
-        self.animations.get(sprite_type)
-            .map(|(_, image, frame_count)| (image.width as u32, *frame_count))
+    pub fn get_sprite_sheet(&self, archetype: usize) -> Option<&SpriteSheet> {
+        self.sheets.get(archetype).and_then(Option::as_ref)
     }
 
-    pub fn get_sprite_pixel_color(&self, sprite_type: &SpriteType, tx: u32, ty: u32) -> Color {
-        if let Some((_, image, _)) = self.animations.get(sprite_type) {
+    // Clips de la hoja del tipo; sin hoja no hay clips y los que se pidan terminan al instante
+    pub fn get_clips(&self, archetype: usize) -> &ClipSet {
+        self.get_sprite_sheet(archetype).map_or(&self.no_clips, |sheet| &sheet.clips)
+    }
+
+    pub fn get_sprite_pixel_color(&self, archetype: usize, tx: u32, ty: u32) -> Color {
+        if let Some(SpriteSheet { image, .. }) = self.get_sprite_sheet(archetype) {
             let x = tx.min(image.width as u32 - 1) as i32;
             let y = ty.min(image.height as u32 - 1) as i32;
             return get_pixel_color(image, x, y);
@@ -67,6 +197,30 @@ impl TextureManager {
         Color::WHITE
     }
 
+    // Carga (una vez) la imagen de un decal; si falla queda un tablero en su lugar y se devuelve el error
+    pub fn load_decal(&mut self, path: &str) -> Result<(), String> {
+        if self.decal_images.contains_key(path) {
+            return Ok(());
+        }
+        let (image, result) = match load_image(path) {
+            Ok(image) => (image, Ok(())),
+            Err(e) => (missing_image(WALL_TEXTURE_SIZE, WALL_TEXTURE_SIZE), Err(e)),
+        };
+        self.decal_images.insert(path.to_string(), image);
+        result
+    }
+
+    pub fn get_decal_size(&self, path: &str) -> Option<(u32, u32)> {
+        self.decal_images.get(path).map(|image| (image.width as u32, image.height as u32))
+    }
+
+    pub fn get_decal_pixel_color(&self, path: &str, tx: u32, ty: u32) -> Color {
+        match self.decal_images.get(path) {
+            Some(image) => get_pixel_color(image, tx as i32, ty as i32),
+            None => TRANSPARENT,
+        }
+    }
+
     // Métodos para texturas estáticas (se mantienen igual)
     pub fn get_pixel_color(&self, ch: char, tx: u32, ty: u32) -> Color {
         if let Some(image) = self.images.get(&ch) {
@@ -83,6 +237,19 @@ impl TextureManager {
     }
 }
 
+// Carga una imagen en RGBA de 8 bits, el formato que lee `get_pixel_color` (los PNG pueden venir en RGB,
+// grises o con paleta)
+fn load_image(path: &str) -> Result<Image, String> {
+    let mut image = Image::load_image(path)?;
+    image.set_format(PixelFormat::PIXELFORMAT_PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+    Ok(image)
+}
+
+// Reemplazo de una imagen que falta: un tablero magenta y negro (casillas de 16 píxeles) bien visible
+fn missing_image(width: i32, height: i32) -> Image {
+    Image::gen_image_checked(width, height, 16, 16, Color::MAGENTA, Color::BLACK)
+}
+
 fn get_pixel_color(image: &Image, x: i32, y: i32) -> Color {
     let width = image.width as usize;
     let height = image.height as usize;
@@ -91,6 +258,11 @@ fn get_pixel_color(image: &Image, x: i32, y: i32) -> Color {
         return Color::new(255, 0, 255, 255); // Magenta para debug
     }
 
+    // Los datos se leen como 4 bytes por píxel: cualquier otro formato se marca en vez de leer de más
+    if image.format() != PixelFormat::PIXELFORMAT_PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 {
+        return Color::new(255, 0, 255, 255);
+    }
+
     let x = x as usize;
     let y = y as usize;
 